@@ -187,6 +187,7 @@ pub fn ui(
             menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
                     let clipboard_text = "Copy map to clipboard";
+                    let clipboard_group_text = "Copy map to clipboard as group";
                     match &meshgen.0 {
                         Some(Ok(brushes)) => {
                             if ui.button(clipboard_text).on_hover_text("Copy the map to the clipboard. You can then paste the curve directly into your level in a program like Trenchbroom.").clicked() {
@@ -198,9 +199,21 @@ pub fn ui(
                                 info!("Copied map to clipboard");
                                 ui.close_menu();
                             };
+                            if ui.button(clipboard_group_text).on_hover_text("Copy the map to the clipboard with the curve inside a Trenchbroom group, so it can be selected and moved as one object.").clicked() {
+                                let simple_worldspawn = SimpleWorldspawn::new(Vec::new());
+                                let entity = QEntity::from(simple_worldspawn);
+                                let map = QMap::new(vec![entity])
+                                    .with_tb_neverball_metadata()
+                                    .with_tb_group(args.selected_curve.to_string(), brushes.clone());
+                                let mapstr = map.to_string();
+                                write_to_clipboard(mapstr);
+                                info!("Copied map to clipboard as group");
+                                ui.close_menu();
+                            };
                         }
                         _ => {
                             ui.add_enabled_ui(false, |ui| ui.button(clipboard_text));
+                            ui.add_enabled_ui(false, |ui| ui.button(clipboard_group_text));
                         }
                     };
                 });
@@ -12,7 +12,7 @@ fn brushes_to_string(brushes: Vec<Brush>) -> String {
     let simple_worldspawn = SimpleWorldspawn::new(brushes);
     let entity = QEntity::from(simple_worldspawn);
     let map = QMap::new(vec![entity]).with_tb_neverball_metadata();
-    String::from(format!("{map}"))
+    format!("{map}")
 }
 
 fn main() {
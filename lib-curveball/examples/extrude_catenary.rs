@@ -15,7 +15,7 @@ fn brushes_to_string(brushes: Vec<Brush>) -> CurveResult<String> {
     let simple_worldspawn = SimpleWorldspawn::new(brushes);
     let entity = QEntity::from(simple_worldspawn);
     let map = QMap::new(vec![entity]).with_tb_neverball_metadata();
-    Ok(String::from(format!("{map}")))
+    Ok(format!("{map}"))
 }
 
 fn main() {
@@ -0,0 +1,53 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use glam::DVec3;
+use lib_curveball::curve::Curve;
+use lib_curveball::curve::curve_classic::CurveClassic;
+use lib_curveball::map::{
+    entity::{BallStart, Coin, Goal, ParScores, Target, View, Worldspawn},
+    qmap::{QEntity, QMap},
+};
+use std::time::Duration;
+
+fn main() {
+    let curve_classic = CurveClassic {
+        n: 12,
+        ri0: 32.0,
+        ro0: 128.0,
+        ri1: 32.0,
+        ro1: 128.0,
+        theta0: 0.0,
+        theta1: 180.0,
+        t: 8.0,
+    };
+    let brushes = curve_classic.bake().unwrap();
+
+    let worldspawn = Worldspawn {
+        message: Some(String::from("Around the bend.")),
+        time: Some(Duration::from_secs(60)),
+        goal: 5,
+        time_par: Some(ParScores::new(
+            Duration::from_secs(10),
+            Duration::from_secs(20),
+        )),
+        coin_par: Some(ParScores::new(5, 5)),
+        ..Worldspawn::new(brushes)
+    };
+
+    let mut entities = vec![
+        QEntity::try_from(worldspawn).unwrap(),
+        BallStart::new(DVec3::new(80.0, 0.0, 16.0)).into(),
+        Goal::new(DVec3::new(-80.0, 0.0, 0.0)).into(),
+        View::new(DVec3::new(0.0, -256.0, 256.0), "center").into(),
+        Target::new(DVec3::ZERO, "center").into(),
+    ];
+    for i in 1..=5 {
+        let angle = (i as f64 * 30.0).to_radians();
+        let origin = DVec3::new(angle.cos(), angle.sin(), 0.0) * 80.0 + DVec3::Z * 16.0;
+        entities.push(Coin::new(origin, 1).into());
+    }
+
+    let map = QMap::new(entities).with_tb_neverball_metadata();
+    println!("{map}");
+}
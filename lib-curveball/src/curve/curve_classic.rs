@@ -3,18 +3,18 @@
 
 //! A circular arc with possibly different start and end radii.
 
-use crate::curve::{Curve, CurveResult, MAX_HULL_ITER};
+use crate::curve::{Curve, CurveResult, bake_segments, ring_edge_role, tag_segment};
 use crate::map::geometry::Brush;
+use crate::map::hull::MAX_HULL_ITER;
 use glam::DVec3;
-use itertools::{Itertools, izip};
+use itertools::izip;
 use lerp::LerpIter;
 use thiserror::Error;
 
 use std::f64::consts::PI;
 
-use super::CurveError;
-
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CurveClassic {
     pub n: u32,
     pub ri0: f64,
@@ -45,7 +45,7 @@ impl Curve for CurveClassic {
         let ro_iter = self.ro0.lerp_iter_closed(self.ro1, n_iter);
         let theta_iter = self.theta0.lerp_iter_closed(self.theta1, n_iter);
 
-        izip!(ri_iter, ro_iter, theta_iter)
+        let rings: Vec<[DVec3; 4]> = izip!(ri_iter, ro_iter, theta_iter)
             .map(|(i_ri, i_ro, i_theta)| {
                 let p_ri_top = DVec3 {
                     x: i_ri * deg2rad(i_theta).cos(),
@@ -73,13 +73,24 @@ impl Curve for CurveClassic {
 
                 [p_ri_top, p_ri_bot, p_ro_top, p_ro_bot]
             })
-            .tuple_windows()
-            .map(|(f1, f2)| {
-                let vertices: Vec<DVec3> = f1.into_iter().chain(f2).collect();
-                Brush::try_from_vertices(&vertices, MAX_HULL_ITER)
-            })
-            .map(|brush_result| brush_result.map_err(CurveError::from))
-            .collect()
+            .collect();
+
+        bake_segments(self.n as usize, |i| {
+            let (f1, f2) = (rings[i], rings[i + 1]);
+            // Go around each end of the segment rather than across it.
+            let ring =
+                |[ri_top, ri_bot, ro_top, ro_bot]: [DVec3; 4]| [ri_top, ri_bot, ro_bot, ro_top];
+            let mut brush = Brush::try_from_rings(&ring(f1), &ring(f2), MAX_HULL_ITER)?;
+            tag_segment(
+                &mut brush,
+                &f1,
+                &f2,
+                i == 0,
+                i + 1 == self.n as usize,
+                ring_edge_role,
+            );
+            Ok(brush)
+        })
     }
 }
 
@@ -3,18 +3,18 @@
 
 //! A highly configurable circular arc.
 
-use crate::curve::{Curve, CurveResult, MAX_HULL_ITER};
+use crate::curve::{Curve, CurveResult, bake_segments, ring_edge_role, tag_segment};
 use crate::map::geometry::Brush;
+use crate::map::hull::MAX_HULL_ITER;
 use glam::DVec3;
-use itertools::{Itertools, izip};
+use itertools::izip;
 use lerp::LerpIter;
 use thiserror::Error;
 
 use std::f64::consts::PI;
 
-use super::CurveError;
-
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CurveSlope {
     pub n: u32,
     pub ri0: f64,
@@ -69,7 +69,7 @@ impl Curve for CurveSlope {
             .lerp_iter_closed(self.height_outer_bot_1, n_iter);
         let hill_iter = (-PI).lerp_iter_closed(PI, n_iter);
 
-        izip!(
+        let rings: Vec<[DVec3; 4]> = izip!(
             ri_iter,
             ro_iter,
             theta_iter,
@@ -122,29 +122,30 @@ impl Curve for CurveSlope {
                 [p_ri_top, p_ri_bot, p_ro_top, p_ro_bot]
             },
         )
-        .tuple_windows()
-        .flat_map(
-            |(
-                [f1_ri_top, f1_ri_bot, f1_ro_top, f1_ro_bot],
-                [f2_ri_top, f2_ri_bot, f2_ro_top, f2_ro_bot],
-            )| {
-                let brush1 = Brush::try_from_vertices(
-                    &[
-                        f1_ri_top, f1_ri_bot, f1_ro_top, f1_ro_bot, f2_ro_top, f2_ro_bot,
-                    ],
-                    MAX_HULL_ITER,
-                );
-                let brush2 = Brush::try_from_vertices(
-                    &[
-                        f1_ri_top, f1_ri_bot, f2_ri_top, f2_ri_bot, f2_ro_top, f2_ro_bot,
-                    ],
-                    MAX_HULL_ITER,
-                );
-                [brush1, brush2].into_iter()
-            },
-        )
-        .map(|brush_result| brush_result.map_err(CurveError::from))
-        .collect()
+        .collect();
+
+        let pairs = bake_segments(self.n as usize, |i| {
+            let (f1, f2) = (rings[i], rings[i + 1]);
+            let [f1_ri_top, f1_ri_bot, f1_ro_top, f1_ro_bot] = f1;
+            let [f2_ri_top, f2_ri_bot, f2_ro_top, f2_ro_bot] = f2;
+            let (first, last) = (i == 0, i + 1 == self.n as usize);
+            let mut brush1 = Brush::try_from_vertices(
+                &[
+                    f1_ri_top, f1_ri_bot, f1_ro_top, f1_ro_bot, f2_ro_top, f2_ro_bot,
+                ],
+                MAX_HULL_ITER,
+            )?;
+            tag_segment(&mut brush1, &f1, &f2, first, last, ring_edge_role);
+            let mut brush2 = Brush::try_from_vertices(
+                &[
+                    f1_ri_top, f1_ri_bot, f2_ri_top, f2_ri_bot, f2_ro_top, f2_ro_bot,
+                ],
+                MAX_HULL_ITER,
+            )?;
+            tag_segment(&mut brush2, &f1, &f2, first, last, ring_edge_role);
+            Ok([brush1, brush2])
+        })?;
+        Ok(pairs.into_iter().flatten().collect())
     }
 }
 
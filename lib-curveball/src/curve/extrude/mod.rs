@@ -4,20 +4,62 @@
 //! Functions to produce curves by extruding a 2D profile along a path in 3D space.
 
 use crate::curve::extrude::path::Path;
-use crate::curve::{CurveError, CurveResult, MAX_HULL_ITER};
+use crate::curve::{CurveResult, bake_segments, tag_segment};
 use crate::map::geometry::Brush;
+use crate::map::hull::MAX_HULL_ITER;
+use crate::map::material::SurfaceRole;
 use glam::{DMat3, DVec2, DVec3};
-use itertools::Itertools;
 use lerp::LerpIter;
 use profile::{CompoundProfile, Profile};
 use thiserror::Error;
 
+// Implement `Serialize` and `Deserialize` for a type in terms of the arguments to its `new()`
+// constructor, so deserialized values are checked the same way as constructed ones. Use `try` for
+// constructors returning a `Result`. Declared before the modules below so they can use it.
+#[cfg(feature = "serde")]
+macro_rules! serde_via_new {
+    ($ty:ident { $($field:ident: $field_ty:ty),* $(,)? }) => {
+        serde_via_new!(@impl $ty { $($field: $field_ty),* } |args| Ok::<_, std::convert::Infallible>(args));
+    };
+    (try $ty:ident { $($field:ident: $field_ty:ty),* $(,)? }) => {
+        serde_via_new!(@impl $ty { $($field: $field_ty),* } |args| args);
+    };
+    (@impl $ty:ident { $($field:ident: $field_ty:ty),* } |$args:ident| $result:expr) => {
+        const _: () = {
+            #[derive(::serde::Serialize, ::serde::Deserialize)]
+            #[serde(deny_unknown_fields)]
+            struct Args {
+                $($field: $field_ty),*
+            }
+
+            impl ::serde::Serialize for $ty {
+                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    Args { $($field: self.$field.clone()),* }.serialize(serializer)
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for $ty {
+                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let Args { $($field),* } = Args::deserialize(deserializer)?;
+                    let $args = Self::new($($field),*);
+                    $result.map_err(::serde::de::Error::custom)
+                }
+            }
+        };
+    };
+}
+
+pub mod mover;
 pub mod path;
+pub mod place;
 pub mod profile;
+pub mod texture;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProfileOrientation {
     Constant(ProfilePlane),
+    #[default]
     FollowPath,
 }
 
@@ -33,6 +75,7 @@ impl std::fmt::Display for ProfileOrientation {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProfilePlane {
     XZ,
     YZ,
@@ -59,10 +102,27 @@ fn make_3d(point_2d: DVec2, plane: ProfilePlane) -> DVec3 {
     }
 }
 
-impl Default for ProfileOrientation {
-    fn default() -> Self {
-        Self::FollowPath
-    }
+// Position a point of a profile in 3D space, the same way every point of a profile is placed when
+// it is extruded.
+pub(crate) fn place_profile_point(
+    profile_point_2d: DVec2,
+    path_point: DVec3,
+    frenet_frame: FrenetFrame,
+    profile_orientation: ProfileOrientation,
+) -> DVec3 {
+    let profile_point_3d = match profile_orientation {
+        ProfileOrientation::Constant(plane) => make_3d(profile_point_2d, plane),
+        ProfileOrientation::FollowPath => {
+            let unrotated_point_3d = make_3d(profile_point_2d, ProfilePlane::YZ);
+            let rmat = DMat3::from_cols(
+                frenet_frame.tangent,
+                frenet_frame.normal,
+                frenet_frame.binormal,
+            );
+            rmat.mul_vec3(unrotated_point_3d)
+        }
+    };
+    profile_point_3d + path_point
 }
 
 /// A [Frenet frame](https://en.wikipedia.org/wiki/Frenet%E2%80%93Serret_formulas) used to describe the orientation of a profile along a path.
@@ -106,9 +166,9 @@ where
     let start = 0.0;
     let end = 1.0;
 
-    // Iterate over every point in the path.
-    // Work on windows of two consecutive points along the path at a time.
-    start
+    // Place the profile at every point along the path, then build a brush between each
+    // consecutive pair.
+    let rings: Vec<(Vec<DVec3>, Vec<DVec2>)> = start
         .lerp_iter_closed(end, n as usize + 1)
         .map(|t| {
             let path_point = path.point(t);
@@ -117,30 +177,31 @@ where
             let face: Vec<DVec3> = this_face
                 .iter()
                 .map(|profile_point_2d| {
-                    let profile_point_3d = match profile_orientation {
-                        ProfileOrientation::Constant(plane) => make_3d(*profile_point_2d, plane),
-                        ProfileOrientation::FollowPath => {
-                            let unrotated_point_3d = make_3d(*profile_point_2d, ProfilePlane::YZ);
-                            let rmat = DMat3::from_cols(
-                                frenet_frame.tangent,
-                                frenet_frame.normal,
-                                frenet_frame.binormal,
-                            );
-                            rmat.mul_vec3(unrotated_point_3d)
-                        }
-                    };
-                    profile_point_3d + path_point
+                    place_profile_point(
+                        *profile_point_2d,
+                        path_point,
+                        frenet_frame,
+                        profile_orientation,
+                    )
                 })
                 .collect();
-            face
-        })
-        .tuple_windows()
-        .map(|(face1, face2)| {
-            let vertices: Vec<DVec3> = face1.into_iter().chain(face2).collect();
-            Brush::try_from_vertices(&vertices, MAX_HULL_ITER)
+            (face, this_face)
         })
-        .map(|brush_result| brush_result.map_err(CurveError::from))
-        .collect()
+        .collect();
+
+    bake_segments(n as usize, |i| {
+        let ((face1, profile1), (face2, _)) = (&rings[i], &rings[i + 1]);
+        let mut brush = Brush::try_from_rings(face1, face2, MAX_HULL_ITER)?;
+        tag_segment(
+            &mut brush,
+            face1,
+            face2,
+            i == 0,
+            i + 1 == n as usize,
+            |a, b| profile_edge_role(std::slice::from_ref(profile1), 0, a, b),
+        );
+        Ok(brush)
+    })
 }
 
 /// Extrude a compound 2D profile along a 3D path.
@@ -178,62 +239,167 @@ where
     let start = 0.0;
     let end = 1.0;
 
-    // Iterate over every point in the path.
-    // Work on windows of two consecutive points along the path at a time.
-    let brushes: Result<Vec<Vec<_>>, _> = start
+    // Place the profile at every point along the path, then build brushes between each
+    // consecutive pair.
+    let rings: Vec<_> = start
         .lerp_iter_closed(end, n as usize + 1)
         .map(|t| {
             let path_point = path.point(t);
             let frenet_frame = path.frame(t);
             let these_faces = compound_profile.compound_profile(t);
             let mut faces: Vec<Vec<DVec3>> = Vec::new();
-            for this_face in these_faces {
+            for this_face in &these_faces {
                 let face: Vec<DVec3> = this_face
                     .iter()
                     .map(|profile_point_2d| {
-                        let profile_point_3d = match profile_orientation {
-                            ProfileOrientation::Constant(plane) => {
-                                make_3d(*profile_point_2d, plane)
-                            }
-                            ProfileOrientation::FollowPath => {
-                                let unrotated_point_3d =
-                                    make_3d(*profile_point_2d, ProfilePlane::YZ);
-                                let rmat = DMat3::from_cols(
-                                    frenet_frame.tangent,
-                                    frenet_frame.normal,
-                                    frenet_frame.binormal,
-                                );
-                                rmat.mul_vec3(unrotated_point_3d)
-                            }
-                        };
-                        profile_point_3d + path_point
+                        place_profile_point(
+                            *profile_point_2d,
+                            path_point,
+                            frenet_frame,
+                            profile_orientation,
+                        )
                     })
                     .collect();
                 faces.push(face);
             }
-            faces
-        })
-        .tuple_windows()
-        .map(|(faces1, faces2)| {
-            let brushes: Result<Vec<_>, _> = faces1
-                .into_iter()
-                .zip(faces2)
-                .map(|(face1, face2)| {
-                    let vertices: Vec<DVec3> = face1.into_iter().chain(face2).collect();
-                    Brush::try_from_vertices(&vertices, MAX_HULL_ITER)
-                })
-                .collect();
-            brushes
+            (faces, these_faces)
         })
-        .map(|brush_result| brush_result.map_err(CurveError::from))
         .collect();
-    brushes.map(|brushes| brushes.into_iter().flatten().collect())
+
+    let brushes = bake_segments(n as usize, |i| {
+        let ((faces1, profiles1), (faces2, _)) = (&rings[i], &rings[i + 1]);
+        faces1
+            .iter()
+            .zip(faces2)
+            .enumerate()
+            .map(|(polygon, (face1, face2))| -> CurveResult<Brush> {
+                let mut brush = Brush::try_from_rings(face1, face2, MAX_HULL_ITER)?;
+                tag_segment(
+                    &mut brush,
+                    face1,
+                    face2,
+                    i == 0,
+                    i + 1 == n as usize,
+                    |a, b| profile_edge_role(profiles1, polygon, a, b),
+                );
+                Ok(brush)
+            })
+            .collect::<CurveResult<Vec<_>>>()
+    })?;
+    Ok(brushes.into_iter().flatten().collect())
+}
+
+/// Decide the role of the side spanning points `a` and `b` of one polygon in a profile.
+///
+/// The role follows the direction the edge faces within the profile: `+y` is the top, `-y` the
+/// bottom, `+x` the outer wall, and `-x` the inner wall. An edge shared with another polygon of a
+/// compound profile lies inside the curve and has no role.
+fn profile_edge_role(
+    profiles: &[Vec<DVec2>],
+    polygon: usize,
+    a: usize,
+    b: usize,
+) -> Option<SurfaceRole> {
+    let profile = &profiles[polygon];
+    let (pa, pb) = (profile[a], profile[b]);
+
+    let shared = profiles.iter().enumerate().any(|(other, other_profile)| {
+        other != polygon
+            && [pa, pb].iter().all(|p| {
+                other_profile
+                    .iter()
+                    .any(|other_p| other_p.distance(*p) < PROFILE_EPSILON)
+            })
+    });
+    if shared {
+        return None;
+    }
+
+    let centroid = profile.iter().sum::<DVec2>() / profile.len() as f64;
+    let mut normal = (pb - pa).perp();
+    if normal.dot((pa + pb) / 2.0 - centroid) < 0.0 {
+        normal = -normal;
+    }
+    if normal.y.abs() >= normal.x.abs() {
+        Some(if normal.y > 0.0 {
+            SurfaceRole::Top
+        } else {
+            SurfaceRole::Bottom
+        })
+    } else {
+        Some(if normal.x > 0.0 {
+            SurfaceRole::OuterWall
+        } else {
+            SurfaceRole::InnerWall
+        })
+    }
 }
 
+const PROFILE_EPSILON: f64 = 0.000000001;
+
 #[derive(Error, Debug)]
 pub enum ExtrudeError {
     #[error("n = {n}. Number of segments must be at least 1.")]
     NotEnoughSegments { n: u32 },
     #[error("n = {n}. Number of segments must be no greater than 4096.")]
     TooManySegments { n: u32 },
+    #[error("{brushes} brushes cannot be shared evenly between {n} segments.")]
+    UnevenSegments { brushes: usize, n: u32 },
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use super::*;
+        use path::{Catenary, Revolve};
+        use profile::{Anchor9Point, Rectangle};
+
+        let path: Revolve =
+            serde_json::from_str(r#"{ "radius": 128.0, "start_angle": 0.0, "end_angle": 90.0 }"#)
+                .unwrap();
+        assert!(path.point(1.0).distance(DVec3::new(0.0, 128.0, 0.0)) < 1e-9);
+        assert_eq!(
+            serde_json::to_string(&path).unwrap(),
+            r#"{"radius":128.0,"start_angle":0.0,"end_angle":90.0}"#
+        );
+
+        let catenary = Catenary::new(256.0, 32.0, 300.0).unwrap();
+        let json = serde_json::to_string(&catenary).unwrap();
+        assert_eq!(json, r#"{"span":256.0,"height":32.0,"s":300.0}"#);
+        let catenary2: Catenary = serde_json::from_str(&json).unwrap();
+        assert_eq!(catenary.point(0.3), catenary2.point(0.3));
+
+        let profile = Rectangle::new(32.0, 8.0, Anchor9Point::TopCenter).unwrap();
+        let json = serde_json::to_string(&profile).unwrap();
+        let profile2: Rectangle = serde_json::from_str(&json).unwrap();
+        assert_eq!(profile.profile(0.0), profile2.profile(0.0));
+
+        let orientation = ProfileOrientation::Constant(ProfilePlane::XZ);
+        let json = serde_json::to_string(&orientation).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ProfileOrientation>(&json).unwrap(),
+            orientation
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_validates() {
+        use super::path::{Catenary, Serpentine};
+        use super::profile::Circle;
+
+        let error = serde_json::from_str::<Circle>(r#"{ "n": 0, "radius": 32.0 }"#).unwrap_err();
+        assert!(error.to_string().contains("at least 1"));
+        assert!(serde_json::from_str::<Serpentine>(r#"{ "x": 64.0, "z": 128.0 }"#).is_err());
+        assert!(
+            serde_json::from_str::<Catenary>(r#"{ "span": 256.0, "height": 0.0, "s": 10.0 }"#)
+                .is_err()
+        );
+        // Misspelled fields are caught rather than ignored.
+        assert!(
+            serde_json::from_str::<Circle>(r#"{ "n": 8, "radius": 32.0, "raduis": 1.0 }"#).is_err()
+        );
+    }
 }
@@ -0,0 +1,229 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Turning a [Path] into the route of a moving platform.
+
+use crate::curve::extrude::path::Path;
+use crate::map::entity::{PathCorner, Train};
+use crate::map::geometry::Brush;
+use crate::map::qmap::QEntity;
+use itertools::Itertools;
+use lerp::LerpIter;
+use thiserror::Error;
+
+/// What a mover does once it reaches the end of its path.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PathRepeat {
+    /// Stop at the end of the path.
+    #[default]
+    Stop,
+    /// Return to the start of the path and go around again. If the path ends where it started,
+    /// the mover carries on smoothly; otherwise it travels straight back to the start.
+    Loop,
+    /// Follow the path back to the start and go around again.
+    PingPong,
+}
+
+impl std::fmt::Display for PathRepeat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stop => write!(f, "Stop"),
+            Self::Loop => write!(f, "Loop"),
+            Self::PingPong => write!(f, "Ping Pong"),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum MoverError {
+    #[error("n = {n}. Number of segments must be at least 1.")]
+    NotEnoughSegments { n: u32 },
+    #[error("n = {n}. Number of segments must be no greater than 4096.")]
+    TooManySegments { n: u32 },
+    #[error("Speed {0} must be greater than zero")]
+    NonPositiveSpeed(f64),
+    #[error("There is no corner {index}")]
+    NoSuchCorner { index: usize },
+}
+
+/// A [Train] together with the chain of [PathCorner]s it follows.
+///
+/// Corners are listed in the order the train visits them, starting from the one the train starts
+/// at. Their fields may be edited freely, e.g. to change the time taken between two corners.
+#[derive(Debug, Clone)]
+pub struct Mover {
+    pub train: Train,
+    pub corners: Vec<PathCorner>,
+}
+
+impl Mover {
+    /// Make the train wait `seconds` at the corner at `index` before moving on.
+    ///
+    /// Neverball has no notion of waiting at a corner, so this adds a second corner at the same
+    /// position for the train to spend `seconds` travelling to.
+    pub fn wait(&mut self, index: usize, seconds: f64) -> Result<(), MoverError> {
+        let corner = self
+            .corners
+            .get_mut(index)
+            .ok_or(MoverError::NoSuchCorner { index })?;
+        let waited = PathCorner {
+            name: format!("{}_wait", corner.name),
+            ..corner.clone()
+        };
+        corner.target = Some(waited.name.clone());
+        corner.speed = seconds;
+        self.corners.insert(index + 1, waited);
+        Ok(())
+    }
+
+    /// The train followed by its corners, ready to add to a map.
+    pub fn entities(self) -> Vec<QEntity> {
+        std::iter::once(self.train.into())
+            .chain(self.corners.into_iter().map(QEntity::from))
+            .collect()
+    }
+}
+
+/// Sample a path into a chain of `n + 1` [PathCorner]s and a [Train] carrying `brushes` along
+/// them.
+///
+/// `name`: Prefix for the names of the corners, which must be unique within the map
+///
+/// `speed`: How fast the train moves along the path, in units per second
+///
+/// `repeat`: What the train does once it reaches the end of the path
+pub fn mover<PTH>(
+    n: u32,
+    path: &PTH,
+    name: &str,
+    speed: f64,
+    repeat: PathRepeat,
+    brushes: Vec<Brush>,
+) -> Result<Mover, MoverError>
+where
+    PTH: Path,
+{
+    if n < 1 {
+        return Err(MoverError::NotEnoughSegments { n });
+    }
+    if n > 4096 {
+        return Err(MoverError::TooManySegments { n });
+    }
+    if speed.is_nan() || speed <= 0.0 {
+        return Err(MoverError::NonPositiveSpeed(speed));
+    }
+
+    let ts: Vec<f64> = 0.0.lerp_iter_closed(1.0, n as usize + 1).collect();
+    let mut points: Vec<_> = ts.iter().map(|&t| path.point(t)).collect();
+    let mut times: Vec<f64> = ts
+        .iter()
+        .tuple_windows()
+        .map(|(&t0, &t1)| path.arc_length(t0, t1) / speed)
+        .collect();
+
+    match repeat {
+        PathRepeat::Stop => (),
+        PathRepeat::Loop => {
+            let (first, last) = (points[0], points[n as usize]);
+            if first.distance(last) < LOOP_EPSILON {
+                points.pop();
+            } else {
+                times.push(first.distance(last) / speed);
+            }
+        }
+        PathRepeat::PingPong => {
+            let back = points.len() - 1;
+            points.extend(points[1..back].iter().rev().copied().collect_vec());
+            times.extend(times.iter().rev().copied().collect_vec());
+        }
+    }
+
+    let corner_name = |i: usize| format!("{name}_{i}");
+    let count = points.len();
+    let corners = points
+        .into_iter()
+        .enumerate()
+        .map(|(i, point)| {
+            let next = times.get(i).map(|_| corner_name((i + 1) % count));
+            PathCorner::new(
+                point,
+                corner_name(i),
+                next,
+                times.get(i).copied().unwrap_or(0.0),
+            )
+        })
+        .collect();
+
+    Ok(Mover {
+        train: Train::new(corner_name(0), brushes),
+        corners,
+    })
+}
+
+const LOOP_EPSILON: f64 = 0.000001;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::extrude::path::{Line, Revolve};
+    use glam::DVec3;
+
+    fn targets(mover: &Mover) -> Vec<Option<&str>> {
+        mover
+            .corners
+            .iter()
+            .map(|corner| corner.target.as_deref())
+            .collect()
+    }
+
+    #[test]
+    fn stop_and_ping_pong() {
+        let path = Line::new(300.0, 0.0, 0.0);
+        let mover = mover(3, &path, "lift", 50.0, PathRepeat::Stop, Vec::new()).unwrap();
+        assert_eq!(mover.train.target, "lift_0");
+        assert_eq!(
+            targets(&mover),
+            [Some("lift_1"), Some("lift_2"), Some("lift_3"), None]
+        );
+        assert_eq!(mover.corners[3].origin, DVec3::new(300.0, 0.0, 0.0));
+        assert!((mover.corners[0].speed - 2.0).abs() < 1e-9);
+
+        let mover = super::mover(3, &path, "lift", 50.0, PathRepeat::PingPong, Vec::new()).unwrap();
+        assert_eq!(mover.corners.len(), 6);
+        assert_eq!(mover.corners[5].target.as_deref(), Some("lift_0"));
+        assert_eq!(mover.corners[4].origin, mover.corners[2].origin);
+        let total: f64 = mover.corners.iter().map(|corner| corner.speed).sum();
+        assert!((total - 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn loop_and_wait() {
+        // A full circle ends where it started, so the last corner is dropped.
+        let path = Revolve::new(64.0, 0.0, 360.0);
+        let mut mover = mover(4, &path, "ring", 10.0, PathRepeat::Loop, Vec::new()).unwrap();
+        assert_eq!(
+            targets(&mover),
+            [
+                Some("ring_1"),
+                Some("ring_2"),
+                Some("ring_3"),
+                Some("ring_0")
+            ]
+        );
+
+        mover.wait(1, 3.0).unwrap();
+        assert_eq!(mover.corners.len(), 5);
+        assert_eq!(mover.corners[1].target.as_deref(), Some("ring_1_wait"));
+        assert_eq!(mover.corners[1].speed, 3.0);
+        assert_eq!(mover.corners[2].origin, mover.corners[1].origin);
+        assert_eq!(mover.corners[2].target.as_deref(), Some("ring_2"));
+        assert_eq!(
+            mover.wait(9, 1.0),
+            Err(MoverError::NoSuchCorner { index: 9 })
+        );
+
+        let entities = mover.entities();
+        assert_eq!(entities[0].parameters["classname"], "func_train");
+        assert_eq!(entities[1].parameters["classname"], "path_corner");
+    }
+}
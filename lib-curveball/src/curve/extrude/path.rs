@@ -6,7 +6,7 @@
 use std::f64::consts::PI;
 
 use glam::{DVec2, DVec3};
-use lerp::Lerp;
+use lerp::{Lerp, LerpIter};
 use thiserror::Error;
 
 use super::FrenetFrame;
@@ -26,8 +26,62 @@ pub trait Path {
     /// `ProfileOrientation::FollowPath`, you may have this function return placeholder vectors
     /// like [DVec3::default].
     fn frame(&self, t: f64) -> FrenetFrame;
+
+    /// The length of the path between the parameters `t0` and `t1`.
+    ///
+    /// The default implementation adds up the lengths of many short chords along the path. Paths
+    /// with a closed-form length may override it.
+    fn arc_length(&self, t0: f64, t1: f64) -> f64 {
+        t0.lerp_iter_closed(t1, ARC_LENGTH_STEPS + 1)
+            .map(|t| self.point(t))
+            .tuple_windows()
+            .map(|(p0, p1)| p0.distance(p1))
+            .sum()
+    }
+}
+
+const ARC_LENGTH_STEPS: usize = 256;
+
+/// The distance along a path at evenly spaced values of `t`, measured with [Path::arc_length], so
+/// a distance along the path can be turned back into `t`.
+pub(crate) struct ArcLengthTable {
+    ts: Vec<f64>,
+    lengths: Vec<f64>,
+}
+
+impl ArcLengthTable {
+    pub(crate) fn new<PTH: Path + ?Sized>(path: &PTH) -> Self {
+        let ts: Vec<f64> = 0.0.lerp_iter_closed(1.0, ARC_LENGTH_STEPS + 1).collect();
+        let lengths = std::iter::once(0.0)
+            .chain(ts.iter().tuple_windows().scan(0.0, |length, (&t0, &t1)| {
+                *length += path.arc_length(t0, t1);
+                Some(*length)
+            }))
+            .collect();
+        Self { ts, lengths }
+    }
+
+    /// The length of the whole path.
+    pub(crate) fn length(&self) -> f64 {
+        *self.lengths.last().unwrap()
+    }
+
+    /// The value of `t` at a distance along the path.
+    pub(crate) fn t_at(&self, length: f64) -> f64 {
+        let i = self
+            .lengths
+            .partition_point(|&l| l < length)
+            .clamp(1, self.lengths.len() - 1);
+        let (l0, l1) = (self.lengths[i - 1], self.lengths[i]);
+        if l1 - l0 < ARC_LENGTH_EPSILON {
+            return self.ts[i];
+        }
+        self.ts[i - 1].lerp(self.ts[i], ((length - l0) / (l1 - l0)).clamp(0.0, 1.0))
+    }
 }
 
+const ARC_LENGTH_EPSILON: f64 = 0.000001;
+
 // Make Box<dyn Path> implement Path
 impl Path for Box<dyn Path + '_> {
     fn point(&self, t: f64) -> DVec3 {
@@ -36,6 +90,9 @@ impl Path for Box<dyn Path + '_> {
     fn frame(&self, t: f64) -> FrenetFrame {
         (**self).frame(t)
     }
+    fn arc_length(&self, t0: f64, t1: f64) -> f64 {
+        (**self).arc_length(t0, t1)
+    }
 }
 
 #[derive(Error, Debug)]
@@ -63,6 +120,13 @@ pub struct Line {
     z: f64,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(Line {
+    x: f64,
+    y: f64,
+    z: f64
+});
+
 impl Line {
     pub fn new(x: f64, y: f64, z: f64) -> Self {
         Self { x, y, z }
@@ -97,6 +161,15 @@ impl Path for Line {
             binormal,
         }
     }
+    fn arc_length(&self, t0: f64, t1: f64) -> f64 {
+        DVec3 {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        }
+        .length()
+            * (t1 - t0).abs()
+    }
 }
 
 // ==================== Revolve ====================
@@ -105,23 +178,34 @@ impl Path for Line {
 #[derive(Debug, Clone)]
 pub struct Revolve {
     radius: f64,
-    start_angle_rad: f64,
-    end_angle_rad: f64,
+    start_angle: f64,
+    end_angle: f64,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(Revolve {
+    radius: f64,
+    start_angle: f64,
+    end_angle: f64
+});
+
 impl Revolve {
     pub fn new(radius: f64, start_angle: f64, end_angle: f64) -> Self {
         Self {
             radius,
-            start_angle_rad: start_angle * PI / 180.0,
-            end_angle_rad: end_angle * PI / 180.0,
+            start_angle,
+            end_angle,
         }
     }
+
+    fn theta(&self, t: f64) -> f64 {
+        self.start_angle.lerp(self.end_angle, t) * PI / 180.0
+    }
 }
 
 impl Path for Revolve {
     fn point(&self, t: f64) -> DVec3 {
-        let theta = self.start_angle_rad.lerp(self.end_angle_rad, t);
+        let theta = self.theta(t);
         DVec3 {
             x: self.radius * theta.cos(),
             y: self.radius * theta.sin(),
@@ -129,7 +213,7 @@ impl Path for Revolve {
         }
     }
     fn frame(&self, t: f64) -> FrenetFrame {
-        let theta = self.start_angle_rad.lerp(self.end_angle_rad, t);
+        let theta = self.theta(t);
         FrenetFrame {
             tangent: DVec3 {
                 x: -theta.sin(),
@@ -148,6 +232,9 @@ impl Path for Revolve {
             },
         }
     }
+    fn arc_length(&self, t0: f64, t1: f64) -> f64 {
+        (self.radius * (self.end_angle - self.start_angle) * PI / 180.0 * (t1 - t0)).abs()
+    }
 }
 
 // Period is in units of space.
@@ -165,6 +252,15 @@ pub struct Sinusoid {
     end: f64,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(try Sinusoid {
+    amplitude: f64,
+    period: f64,
+    phase: f64,
+    start: f64,
+    end: f64,
+});
+
 impl Sinusoid {
     pub fn new(amplitude: f64, period: f64, phase: f64, start: f64, end: f64) -> PathResult<Self> {
         if period <= 0.0 {
@@ -230,6 +326,9 @@ pub struct Bezier {
     points: Vec<DVec2>,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(try Bezier { points: Vec<DVec2> });
+
 impl Bezier {
     pub fn new(points: Vec<DVec2>) -> Result<Self, BezierError> {
         if points.len() < 2 {
@@ -314,12 +413,17 @@ fn bezier_derivative(points: &[DVec2], t: f64) -> DVec2 {
 /// hung between two points.
 #[derive(Debug, Clone)]
 pub struct Catenary {
+    span: f64,
+    height: f64,
+    s: f64,
     a: f64,
     k: f64,
     c: f64,
-    span: f64,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(try Catenary { span: f64, height: f64, s: f64 });
+
 impl Catenary {
     pub fn new(span: f64, height: f64, s: f64) -> Result<Self, CatenaryError> {
         // get delta values
@@ -344,7 +448,24 @@ impl Catenary {
         let k: f64 = 0.5 * (h - a * f64::ln((s + v) / (s - v)));
         let c: f64 = -a * f64::cosh((-k) / a);
 
-        Ok(Self { a, k, c, span })
+        Ok(Self {
+            span,
+            height,
+            s,
+            a,
+            k,
+            c,
+        })
+    }
+
+    /// The height of the end point above the start point.
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// The length of the cable, `s` in [Catenary::new].
+    pub fn length(&self) -> f64 {
+        self.s
     }
 }
 
@@ -465,6 +586,9 @@ pub struct Serpentine {
     z: f64,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(try Serpentine { x: f64, z: f64 });
+
 impl Serpentine {
     pub fn new(x: f64, z: f64) -> Result<Self, SerpentineError> {
         if z <= 0.0 {
@@ -0,0 +1,194 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Placing point entities, like coins, along a path.
+
+use crate::curve::extrude::path::{ArcLengthTable, Path};
+use crate::curve::extrude::{ProfileOrientation, place_profile_point};
+use crate::map::qmap::QEntity;
+use glam::{DVec2, DVec3};
+use lerp::{Lerp, LerpIter};
+use thiserror::Error;
+
+/// How far apart to place entities along a path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Spacing {
+    /// Place this many entities an even distance apart, from the start of the path to the end.
+    Count(u32),
+    /// Place an entity every so many units along the path, starting at the start of the path.
+    Distance(f64),
+    /// Place this many entities at evenly spaced values of the path's parameter `t`. This is
+    /// faster than [Spacing::Count], but entities bunch up where the path moves slowly.
+    UniformT(u32),
+}
+
+impl std::fmt::Display for Spacing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Count(count) => write!(f, "{count} evenly spaced"),
+            Self::Distance(distance) => write!(f, "Every {distance} units"),
+            Self::UniformT(count) => write!(f, "{count} at uniform t"),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PlaceError {
+    #[error("Must place at least one entity")]
+    NoEntities,
+    #[error("Spacing {0} must be greater than zero")]
+    NonPositiveDistance(f64),
+    #[error("Placing {count} entities exceeds the maximum of 4096")]
+    TooManyEntities { count: usize },
+}
+
+/// Find evenly spaced points along a path.
+///
+/// `offset`: Where each point sits relative to the path, in the coordinates of a profile extruded
+/// along it. To float coins above the top of a track, give the height of the track's top plus a
+/// little more, e.g. `DVec2::new(0.0, 24.0)`.
+///
+/// `profile_orientation`: The orientation the track was extruded with
+pub fn points_along<PTH>(
+    path: &PTH,
+    spacing: Spacing,
+    offset: DVec2,
+    profile_orientation: ProfileOrientation,
+) -> Result<Vec<DVec3>, PlaceError>
+where
+    PTH: Path,
+{
+    let ts = match spacing {
+        Spacing::UniformT(count) => {
+            check_count(count as usize)?;
+            evenly_spaced(0.0, 1.0, count)
+        }
+        Spacing::Count(count) => {
+            check_count(count as usize)?;
+            let table = ArcLengthTable::new(path);
+            evenly_spaced(0.0, table.length(), count)
+                .into_iter()
+                .map(|s| table.t_at(s))
+                .collect()
+        }
+        Spacing::Distance(distance) => {
+            if distance.is_nan() || distance <= 0.0 {
+                return Err(PlaceError::NonPositiveDistance(distance));
+            }
+            let table = ArcLengthTable::new(path);
+            let count = (table.length() / distance + PLACE_EPSILON).floor() as usize + 1;
+            check_count(count)?;
+            (0..count)
+                .map(|i| table.t_at(i as f64 * distance))
+                .collect()
+        }
+    };
+
+    Ok(ts
+        .into_iter()
+        .map(|t| place_profile_point(offset, path.point(t), path.frame(t), profile_orientation))
+        .collect())
+}
+
+/// Place point entities along a path. See [points_along] for the meaning of the arguments.
+///
+/// `entity` makes the entity to place at each point, e.g. `|point| Coin::new(point, 1)`.
+pub fn place_along<PTH, E>(
+    path: &PTH,
+    spacing: Spacing,
+    offset: DVec2,
+    profile_orientation: ProfileOrientation,
+    entity: impl FnMut(DVec3) -> E,
+) -> Result<Vec<QEntity>, PlaceError>
+where
+    PTH: Path,
+    E: Into<QEntity>,
+{
+    Ok(points_along(path, spacing, offset, profile_orientation)?
+        .into_iter()
+        .map(entity)
+        .map(Into::into)
+        .collect())
+}
+
+fn check_count(count: usize) -> Result<(), PlaceError> {
+    match count {
+        0 => Err(PlaceError::NoEntities),
+        1..=4096 => Ok(()),
+        _ => Err(PlaceError::TooManyEntities { count }),
+    }
+}
+
+// A single entity goes in the middle.
+fn evenly_spaced(start: f64, end: f64, count: u32) -> Vec<f64> {
+    match count {
+        1 => vec![start.lerp(end, 0.5)],
+        _ => start.lerp_iter_closed(end, count as usize).collect(),
+    }
+}
+
+const PLACE_EPSILON: f64 = 0.000001;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::extrude::path::{Bezier, Revolve};
+    use crate::map::entity::Coin;
+    use itertools::Itertools;
+
+    #[test]
+    fn even_spacing() {
+        // The control points bunch up at the start, so uniform t would too.
+        let path = Bezier::new(vec![
+            DVec2::ZERO,
+            DVec2::new(10.0, 0.0),
+            DVec2::new(20.0, 0.0),
+            DVec2::new(300.0, 0.0),
+        ])
+        .unwrap();
+        let orientation = ProfileOrientation::FollowPath;
+        let points = points_along(&path, Spacing::Count(7), DVec2::ZERO, orientation).unwrap();
+        assert_eq!(points.len(), 7);
+        for (p0, p1) in points.iter().tuple_windows() {
+            assert!((p0.distance(*p1) - 50.0).abs() < 0.1);
+        }
+
+        let uniform = points_along(&path, Spacing::UniformT(7), DVec2::ZERO, orientation).unwrap();
+        assert!(uniform[1].distance(uniform[0]) < 40.0);
+
+        let points = points_along(&path, Spacing::Distance(64.0), DVec2::ZERO, orientation);
+        assert_eq!(points.unwrap().len(), 5);
+        assert_eq!(
+            points_along(&path, Spacing::Distance(0.0), DVec2::ZERO, orientation),
+            Err(PlaceError::NonPositiveDistance(0.0))
+        );
+    }
+
+    #[test]
+    fn coins_above_track() {
+        let path = Revolve::new(128.0, 0.0, 90.0);
+        let coins = place_along(
+            &path,
+            Spacing::Count(4),
+            DVec2::new(0.0, 24.0),
+            ProfileOrientation::FollowPath,
+            |point| Coin::new(point, 1),
+        )
+        .unwrap();
+        assert_eq!(coins.len(), 4);
+        assert_eq!(coins[0].parameters["origin"], "128 0 24");
+        assert_eq!(coins[3].parameters["origin"], "0 128 24");
+    }
+
+    #[test]
+    fn exact_arc_length() {
+        // Revolve knows its own length, so a quarter of it fits exactly four times.
+        let path = Revolve::new(128.0, 0.0, 90.0);
+        let quarter = path.arc_length(0.0, 1.0) / 4.0;
+        let orientation = ProfileOrientation::FollowPath;
+        let points = points_along(&path, Spacing::Distance(quarter), DVec2::ZERO, orientation);
+        let points = points.unwrap();
+        assert_eq!(points.len(), 5);
+        assert!(points[4].distance(DVec3::new(0.0, 128.0, 0.0)) < 1e-9);
+    }
+}
@@ -69,6 +69,9 @@ pub struct Circle {
     radius: f64,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(try Circle { n: u32, radius: f64 });
+
 impl Circle {
     pub fn new(n: u32, radius: f64) -> Result<Self, CircleError> {
         if n < 1 {
@@ -108,10 +111,18 @@ pub enum CircleError {
 pub struct CircleSector {
     n: u32,
     radius: f64,
-    start_angle_rad: f64,
-    end_angle_rad: f64,
+    start_angle: f64,
+    end_angle: f64,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(try CircleSector {
+    n: u32,
+    radius: f64,
+    start_angle: f64,
+    end_angle: f64,
+});
+
 impl CircleSector {
     pub fn new(
         n: u32,
@@ -134,16 +145,18 @@ impl CircleSector {
         Ok(Self {
             n,
             radius,
-            start_angle_rad: start_angle * PI / 180.0,
-            end_angle_rad: end_angle * PI / 180.0,
+            start_angle,
+            end_angle,
         })
     }
 }
 
 impl Profile for CircleSector {
     fn profile(&self, _t: f64) -> Vec<DVec2> {
-        self.start_angle_rad
-            .lerp_iter_closed(self.end_angle_rad, self.n as usize + 1)
+        let start_angle = self.start_angle * PI / 180.0;
+        let end_angle = self.end_angle * PI / 180.0;
+        start_angle
+            .lerp_iter_closed(end_angle, self.n as usize + 1)
             .map(|theta| DVec2 {
                 x: self.radius * theta.cos(),
                 y: self.radius * theta.sin(),
@@ -175,6 +188,7 @@ pub enum CircleSectorError {
 /// thus the top-left corner would be touching the path the profile
 /// is being extruded along.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Anchor9Point {
     TopLeft,
     TopCenter,
@@ -195,6 +209,9 @@ pub struct Rectangle {
     anchor: Anchor9Point,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(try Rectangle { width: f64, height: f64, anchor: Anchor9Point });
+
 impl Rectangle {
     pub fn new(width: f64, height: f64, anchor: Anchor9Point) -> Result<Self, RectangleError> {
         Ok(Self {
@@ -255,6 +272,15 @@ pub struct Parallelogram {
     anchor: Anchor9Point,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(try Parallelogram {
+    width: f64,
+    height: f64,
+    offset_x: f64,
+    offset_z: f64,
+    anchor: Anchor9Point,
+});
+
 impl Parallelogram {
     pub fn new(
         width: f64,
@@ -324,6 +350,15 @@ pub struct Annulus {
     end_angle: f64,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(try Annulus {
+    n: u32,
+    inner_radius: f64,
+    outer_radius: f64,
+    start_angle: f64,
+    end_angle: f64,
+});
+
 impl Annulus {
     pub fn new(
         n: u32,
@@ -392,6 +427,9 @@ pub struct Arbitrary {
     points: Vec<Vec<DVec2>>,
 }
 
+#[cfg(feature = "serde")]
+serde_via_new!(Arbitrary { points: Vec<Vec<DVec2>> });
+
 impl Arbitrary {
     pub fn new(points: Vec<Vec<DVec2>>) -> Arbitrary {
         Self { points }
@@ -0,0 +1,247 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Aligning the textures of an extrusion so they follow its path.
+
+use crate::curve::extrude::ExtrudeError;
+use crate::curve::extrude::path::Path;
+use crate::map::geometry::{Brush, SideMtrl, base_texture_axes, texture_plane_components};
+use crate::map::material::SurfaceRole;
+use glam::{DVec2, DVec3};
+use itertools::Itertools;
+use lerp::LerpIter;
+
+/// How the textures of an extrusion should follow its path.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TextureMapping {
+    /// Leave textures projected in world space, as with any other brush.
+    #[default]
+    World,
+    /// Turn textures to follow the path, restarting them at the beginning of every segment.
+    PerSegment,
+    /// Turn textures to follow the path, running them continuously along it by arc length.
+    Continuous,
+}
+
+impl std::fmt::Display for TextureMapping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::World => write!(f, "World"),
+            Self::PerSegment => write!(f, "Per Segment"),
+            Self::Continuous => write!(f, "Continuous"),
+        }
+    }
+}
+
+/// Align the textures on the top, bottom, and wall faces of an extrusion to follow its path.
+///
+/// The texture's `s` axis runs along the path and its `t` axis runs across it, with the texture
+/// offset measured from the path itself. Caps and faces between segments are left alone.
+///
+/// `brushes` must be the output of [extrude](super::extrude) or
+/// [extrude_multi](super::extrude_multi) for the same `n` and `path`, and fails if they cannot be
+/// shared evenly between `n` segments. Each face's scale before it was first aligned is taken as
+/// the scale along the path, so assign materials first (e.g. with a
+/// [MaterialMap](crate::map::material::MaterialMap)). Aligning again gives the same result.
+pub fn align_textures<PTH>(
+    brushes: &mut [Brush],
+    n: u32,
+    path: &PTH,
+    mapping: TextureMapping,
+) -> Result<(), ExtrudeError>
+where
+    PTH: Path,
+{
+    if n < 1 {
+        Err(ExtrudeError::NotEnoughSegments { n })?;
+    }
+    if !brushes.len().is_multiple_of(n as usize) {
+        Err(ExtrudeError::UnevenSegments {
+            brushes: brushes.len(),
+            n,
+        })?;
+    }
+    if mapping == TextureMapping::World {
+        return Ok(());
+    }
+    let brushes_per_segment = brushes.len() / n as usize;
+
+    let mut distance = 0.0;
+    for (segment, (t0, t1)) in 0.0
+        .lerp_iter_closed(1.0, n as usize + 1)
+        .tuple_windows()
+        .enumerate()
+    {
+        let start = path.point(t0);
+        let end = path.point(t1);
+        let segment_length = path.arc_length(t0, t1);
+        let along = match mapping {
+            TextureMapping::Continuous => distance,
+            _ => 0.0,
+        };
+
+        for brush in brushes
+            .iter_mut()
+            .skip(segment * brushes_per_segment)
+            .take(brushes_per_segment)
+        {
+            brush.update_materials(|geom, mtrl| {
+                use SurfaceRole as SR;
+                if !matches!(
+                    mtrl.role,
+                    Some(SR::Top | SR::Bottom | SR::InnerWall | SR::OuterWall)
+                ) {
+                    return;
+                }
+                let Some(normal) = geom.normal() else {
+                    return;
+                };
+                align_side(mtrl, normal, start, end, segment_length, along);
+            });
+        }
+
+        distance += segment_length;
+    }
+    Ok(())
+}
+
+// Rotate and stretch a side's texture so its s axis follows the chord from `start` to `end`, and
+// reads `along` (in world units) at `start`.
+fn align_side(
+    mtrl: &mut SideMtrl,
+    normal: DVec3,
+    start: DVec3,
+    end: DVec3,
+    segment_length: f64,
+    along: f64,
+) {
+    let base = base_texture_axes(normal);
+    let (sv, tv) = texture_plane_components(base);
+    let chord = end - start;
+    let projected = DVec2::new(chord[sv], chord[tv]);
+    let base_s = DVec2::new(base[0][sv], base[0][tv]);
+    if projected.length() < ALIGN_EPSILON || segment_length < ALIGN_EPSILON {
+        return;
+    }
+
+    let rotation = (projected.y.atan2(projected.x) - base_s.y.atan2(base_s.x))
+        .to_degrees()
+        .rem_euclid(360.0);
+    // Start from the alignment the side had before it was first aligned, if it has been.
+    let unaligned = mtrl.unaligned.unwrap_or_else(|| mtrl.alignment());
+    // The texture is projected onto the face, so stretch it to cover the segment's true length.
+    let scale_x = unaligned.scale_x * projected.length() / segment_length;
+
+    let aligned = SideMtrl {
+        rotation,
+        scale_x,
+        scale_y: unaligned.scale_y,
+        offset_x: unaligned.offset_x + along / unaligned.scale_x,
+        offset_y: unaligned.offset_y,
+        unaligned: Some(unaligned),
+        ..mtrl.clone()
+    };
+    let [s_axis, t_axis] = aligned.texture_axes(normal);
+    *mtrl = SideMtrl {
+        offset_x: aligned.offset_x - start.dot(s_axis),
+        offset_y: aligned.offset_y - start.dot(t_axis),
+        ..aligned
+    };
+}
+
+const ALIGN_EPSILON: f64 = 0.000000001;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::extrude::path::{Bezier, Revolve};
+    use crate::curve::extrude::profile::{Anchor9Point, Rectangle};
+    use crate::curve::extrude::{ProfileOrientation, extrude};
+    use crate::map::geometry::Side;
+
+    fn top(brush: &Brush) -> Side {
+        brush
+            .triangles()
+            .find(|side| side.mtrl.role == Some(SurfaceRole::Top))
+            .unwrap()
+    }
+
+    fn texture_coords(side: &Side, point: DVec3) -> DVec2 {
+        let [s, t] = side.mtrl.texture_axes(side.geom.normal().unwrap());
+        DVec2::new(
+            point.dot(s) + side.mtrl.offset_x,
+            point.dot(t) + side.mtrl.offset_y,
+        )
+    }
+
+    #[test]
+    fn arc_length() {
+        let revolve = Revolve::new(128.0, 0.0, 90.0);
+        let expected = 128.0 * std::f64::consts::FRAC_PI_2;
+        assert!((revolve.arc_length(0.0, 1.0) - expected).abs() < 1e-9);
+
+        // The default implementation, approximating a straight line.
+        let bezier = Bezier::new(vec![DVec2::ZERO, DVec2::new(30.0, 40.0)]).unwrap();
+        assert!((bezier.arc_length(0.0, 1.0) - 50.0).abs() < 1e-9);
+        assert!((bezier.arc_length(0.5, 1.0) - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn continuous_mapping() {
+        let n = 8;
+        let path = Revolve::new(128.0, 0.0, 180.0);
+        let profile = Rectangle::new(32.0, 8.0, Anchor9Point::Center).unwrap();
+        let mut brushes = extrude(n, &profile, &path, ProfileOrientation::FollowPath).unwrap();
+        align_textures(&mut brushes, n, &path, TextureMapping::Continuous).unwrap();
+
+        for i in 0..n as usize {
+            let t = (i + 1) as f64 / n as f64;
+            let point = path.point(t);
+            let side = top(&brushes[i]);
+            let coords = texture_coords(&side, point);
+            // The default scale is 0.5 units per texel.
+            assert!((coords.x - path.arc_length(0.0, t) / 0.5).abs() < 1e-6);
+            assert!(coords.y.abs() < 1e-6);
+            if i + 1 < n as usize {
+                let next = texture_coords(&top(&brushes[i + 1]), point);
+                assert!(coords.distance(next) < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn per_segment_mapping() {
+        let n = 4;
+        let path = Revolve::new(128.0, 0.0, 90.0);
+        let profile = Rectangle::new(32.0, 8.0, Anchor9Point::Center).unwrap();
+        let mut brushes = extrude(n, &profile, &path, ProfileOrientation::FollowPath).unwrap();
+        align_textures(&mut brushes, n, &path, TextureMapping::PerSegment).unwrap();
+        // Aligning again leaves the textures where they are.
+        let sides = |brushes: &[Brush]| {
+            brushes
+                .iter()
+                .flat_map(Brush::triangles)
+                .collect::<Vec<_>>()
+        };
+        let once = sides(&brushes);
+        align_textures(&mut brushes, n, &path, TextureMapping::PerSegment).unwrap();
+        assert_eq!(sides(&brushes), once);
+
+        for (i, brush) in brushes.iter().enumerate() {
+            let point = path.point(i as f64 / n as f64);
+            let coords = texture_coords(&top(brush), point);
+            assert!(coords.length() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn uneven_segments() {
+        let path = Revolve::new(128.0, 0.0, 90.0);
+        let profile = Rectangle::new(32.0, 8.0, Anchor9Point::Center).unwrap();
+        let mut brushes = extrude(4, &profile, &path, ProfileOrientation::FollowPath).unwrap();
+        assert!(matches!(
+            align_textures(&mut brushes, 3, &path, TextureMapping::Continuous),
+            Err(ExtrudeError::UnevenSegments { brushes: 4, n: 3 })
+        ));
+    }
+}
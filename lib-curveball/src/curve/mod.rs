@@ -3,7 +3,9 @@
 
 //! A collection of structs and functions that produce a vector of [brushes][Brush].
 
-use crate::map::geometry::Brush;
+use crate::map::geometry::{Brush, SideGeom};
+use crate::map::material::SurfaceRole;
+use glam::DVec3;
 use thiserror::Error;
 
 pub mod curve_classic;
@@ -11,12 +13,84 @@ pub mod curve_slope;
 pub mod extrude;
 pub mod rayto;
 
-const MAX_HULL_ITER: Option<usize> = Some(10_000);
-
 pub trait Curve {
     fn bake(&self) -> CurveResult<Vec<Brush>>;
 }
 
+/// Build `n` segments of a curve by calling `segment` with the index of each.
+///
+/// With the `rayon` feature, segments are built in parallel. Either way they are returned in
+/// order, and if any fail, the error is the one from the earliest segment that failed.
+pub(crate) fn bake_segments<T, F>(n: usize, segment: F) -> CurveResult<Vec<T>>
+where
+    T: Send,
+    F: Fn(usize) -> CurveResult<T> + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    let results: Vec<CurveResult<T>> = {
+        use rayon::prelude::*;
+        (0..n).into_par_iter().map(segment).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let results = (0..n).map(segment);
+
+    results.into_iter().collect()
+}
+
+/// Tag the sides of a brush built between two consecutive rings of points along a curve.
+///
+/// A side lying entirely on the `start` ring is the curve's start cap if this is the `first`
+/// segment, and otherwise has no role; likewise for `end`. Any other side spans an edge between two
+/// points of the ring, and `edge_role` is given the indices of those two points.
+pub(crate) fn tag_segment(
+    brush: &mut Brush,
+    start: &[DVec3],
+    end: &[DVec3],
+    first: bool,
+    last: bool,
+    edge_role: impl Fn(usize, usize) -> Option<SurfaceRole>,
+) {
+    fn ring_index(ring: &[DVec3], point: DVec3) -> Option<usize> {
+        ring.iter()
+            .position(|ring_point| ring_point.distance(point) < RING_EPSILON)
+    }
+
+    let role = |SideGeom(points): SideGeom| {
+        if points.iter().all(|p| ring_index(start, *p).is_some()) {
+            return first.then_some(SurfaceRole::StartCap);
+        }
+        if points.iter().all(|p| ring_index(end, *p).is_some()) {
+            return last.then_some(SurfaceRole::EndCap);
+        }
+        let mut indices: Vec<usize> = points
+            .iter()
+            .filter_map(|p| ring_index(start, *p).or_else(|| ring_index(end, *p)))
+            .collect();
+        indices.sort();
+        indices.dedup();
+        match indices[..] {
+            [a, b] => edge_role(a, b),
+            _ => None,
+        }
+    };
+    brush.update_materials(|geom, mtrl| mtrl.role = role(geom));
+}
+
+/// The role of a side spanning two points of a ring laid out as `[ri_top, ri_bot, ro_top,
+/// ro_bot]`, as built by [CurveClassic](curve_classic::CurveClassic) and
+/// [CurveSlope](curve_slope::CurveSlope).
+pub(crate) fn ring_edge_role(a: usize, b: usize) -> Option<SurfaceRole> {
+    match (a, b) {
+        (0, 2) => Some(SurfaceRole::Top),
+        (1, 3) => Some(SurfaceRole::Bottom),
+        (0, 1) => Some(SurfaceRole::InnerWall),
+        (2, 3) => Some(SurfaceRole::OuterWall),
+        _ => None,
+    }
+}
+
+const RING_EPSILON: f64 = 0.000000001;
+
 #[derive(Error, Debug)]
 pub enum CurveError {
     #[error("{0}")]
@@ -26,7 +100,7 @@ pub enum CurveError {
     CurveSlopeError(#[from] curve_slope::CurveSlopeError),
 
     #[error("Failed to find convex hull: {0}")]
-    ConvexHullFail(#[from] chull::ErrorKind),
+    ConvexHullFail(#[from] crate::map::hull::HullError),
 
     #[error("{0}")]
     RaytoError(#[from] rayto::RaytoError),
@@ -42,3 +116,27 @@ pub enum CurveError {
 }
 
 pub type CurveResult<T> = Result<T, CurveError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_in_order() {
+        let segments = bake_segments(1000, |i| Ok(i * 2)).unwrap();
+        assert!(segments.iter().enumerate().all(|(i, s)| *s == i * 2));
+
+        // The earliest failure is reported, however the segments were scheduled.
+        let error = bake_segments(1000, |i| {
+            if i % 300 == 299 {
+                Err(curve_classic::CurveClassicError::NotEnoughSegments { n: i as u32 })?;
+            }
+            Ok(i)
+        })
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "n = 299. Number of segments must be at least 1."
+        );
+    }
+}
@@ -5,14 +5,17 @@
 //!
 //! Rayto is helpful for creating maps that avoid T-intersections.
 
-use crate::curve::{Curve, CurveResult, MAX_HULL_ITER};
+use crate::curve::{Curve, CurveResult, bake_segments, tag_segment};
 use crate::map::geometry::Brush;
+use crate::map::hull::MAX_HULL_ITER;
+use crate::map::material::SurfaceRole;
 use glam::DVec3;
 use thiserror::Error;
 
 use std::f64::consts::PI;
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rayto {
     pub n: u32,
     pub r0: f64,
@@ -36,9 +39,7 @@ impl Curve for Rayto {
         let dr = (self.r1 - self.r0) / (self.n as f64);
         let dtheta = (self.theta1 - self.theta0) / (self.n as f64);
 
-        let mut brushes = Vec::new();
-
-        for i in 0..self.n {
+        bake_segments(self.n as usize, |i| {
             let rstart = self.r0 + dr * (i as f64);
             let rend = self.r0 + dr * (i as f64 + 1.0);
             let thetastart = self.theta0 + dtheta * (i as f64);
@@ -80,13 +81,28 @@ impl Curve for Rayto {
                 z: self.h,
             };
 
-            brushes.push(Brush::try_from_vertices(
-                &[pa, pb, pc, pd, pe, pf],
-                MAX_HULL_ITER,
-            )?);
-        }
+            let mut brush = Brush::try_from_rings(&[pa, pb, pc], &[pd, pe, pf], MAX_HULL_ITER)?;
+            tag_segment(
+                &mut brush,
+                &[pa, pd, pb, pe],
+                &[pa, pd, pc, pf],
+                i == 0,
+                i + 1 == self.n as usize,
+                edge_role,
+            );
+            Ok(brush)
+        })
+    }
+}
 
-        Ok(brushes)
+// Faces are tagged by the pair of points they span in [bottom center, top center, bottom edge,
+// top edge].
+fn edge_role(a: usize, b: usize) -> Option<SurfaceRole> {
+    match (a, b) {
+        (1, 3) => Some(SurfaceRole::Top),
+        (0, 2) => Some(SurfaceRole::Bottom),
+        (2, 3) => Some(SurfaceRole::OuterWall),
+        _ => None,
     }
 }
 
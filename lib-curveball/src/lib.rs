@@ -14,3 +14,5 @@
 pub mod curve;
 
 pub mod map;
+
+pub mod mesh;
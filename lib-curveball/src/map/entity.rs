@@ -4,7 +4,10 @@
 //! Custom Quake3 entities defined for Neverball.
 use crate::map::geometry::Brush;
 use crate::map::qmap::QEntity;
+use glam::DVec3;
 use std::collections::HashMap;
+use std::time::Duration;
+use thiserror::Error;
 
 /// A basic representation of a `Worldspawn`, an entity defining the static geometry of a level.
 #[derive(Debug, Clone)]
@@ -29,8 +32,679 @@ impl From<SimpleWorldspawn> for QEntity {
     }
 }
 
-// TODO: Add other Neverball entities, like coins
-// TODO: Add TrenchbroomGroup entity
+/// A Neverball `worldspawn`, holding the static geometry of a level along with its metadata.
+///
+/// Fields left as `None` are not written, and Neverball falls back to its defaults. Convert to a
+/// [QEntity] with [TryFrom], which checks the fields with [validate](Self::validate) first.
+#[derive(Debug, Default, Clone)]
+pub struct Worldspawn {
+    pub brushes: Vec<Brush>,
+    /// Text shown before the level starts. Neverball breaks lines at `\\`.
+    pub message: Option<String>,
+    /// Background music, e.g. `bgm/track1.ogg`.
+    pub song: Option<String>,
+    /// Skybox, e.g. `map-back/clouds`.
+    pub back: Option<String>,
+    /// Background gradient image, e.g. `back/gradient.png`.
+    pub grad: Option<String>,
+    /// Time limit. With no limit, the clock counts up instead.
+    pub time: Option<Duration>,
+    /// Number of coins needed to open the goal.
+    pub goal: u32,
+    /// Par times for finishing the level.
+    pub time_par: Option<ParScores<Duration>>,
+    /// Par times for reaching the goal with the most time left.
+    pub goal_par: Option<ParScores<Duration>>,
+    /// Par coin counts for finishing the level.
+    pub coin_par: Option<ParScores<u32>>,
+}
+
+/// The hard and medium scores of one of a level's high score tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParScores<T> {
+    pub hard: T,
+    pub medium: T,
+}
+
+impl<T> ParScores<T> {
+    pub fn new(hard: T, medium: T) -> Self {
+        Self { hard, medium }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum WorldspawnError {
+    #[error("\"{key}\" cannot contain quotes or line breaks")]
+    InvalidText { key: &'static str },
+    #[error("Time limit must be greater than zero; use no limit instead")]
+    ZeroTime,
+    #[error("\"{key}\" hard score must be at least as good as the medium score")]
+    ParOrder { key: &'static str },
+    #[error("\"{key}\" par time exceeds the time limit")]
+    ParExceedsTime { key: &'static str },
+}
+
+impl Worldspawn {
+    pub fn new(brushes: Vec<Brush>) -> Self {
+        Self {
+            brushes,
+            ..Default::default()
+        }
+    }
+
+    /// Check that every field can be written to the map and makes sense to Neverball.
+    pub fn validate(&self) -> Result<(), WorldspawnError> {
+        for (key, text) in [
+            ("message", &self.message),
+            ("song", &self.song),
+            ("back", &self.back),
+            ("grad", &self.grad),
+        ] {
+            if text
+                .as_deref()
+                .is_some_and(|text| text.contains(['"', '\n', '\r']))
+            {
+                return Err(WorldspawnError::InvalidText { key });
+            }
+        }
+
+        if self.time == Some(Duration::ZERO) {
+            return Err(WorldspawnError::ZeroTime);
+        }
+
+        for (key, par) in [("time_hs", &self.time_par), ("goal_hs", &self.goal_par)] {
+            let Some(par) = par else {
+                continue;
+            };
+            // Faster is better.
+            if par.hard > par.medium {
+                return Err(WorldspawnError::ParOrder { key });
+            }
+            if self.time.is_some_and(|time| par.medium > time) {
+                return Err(WorldspawnError::ParExceedsTime { key });
+            }
+        }
+
+        // More coins is better.
+        if self.coin_par.is_some_and(|par| par.hard < par.medium) {
+            return Err(WorldspawnError::ParOrder { key: "coin_hs" });
+        }
+
+        Ok(())
+    }
+}
+
+// Neverball measures time in hundredths of a second.
+fn centiseconds(time: Duration) -> u128 {
+    time.as_millis() / 10
+}
+
+impl TryFrom<Worldspawn> for QEntity {
+    type Error = WorldspawnError;
+
+    fn try_from(item: Worldspawn) -> Result<Self, Self::Error> {
+        item.validate()?;
+
+        let mut parameters = HashMap::new();
+        parameters.insert(String::from("classname"), String::from("worldspawn"));
+        for (key, text) in [
+            ("message", item.message),
+            ("song", item.song),
+            ("back", item.back),
+            ("grad", item.grad),
+        ] {
+            if let Some(text) = text {
+                parameters.insert(String::from(key), text);
+            }
+        }
+        if let Some(time) = item.time {
+            parameters.insert(String::from("time"), centiseconds(time).to_string());
+        }
+        parameters.insert(String::from("goal"), item.goal.to_string());
+        for (key, par) in [("time_hs", item.time_par), ("goal_hs", item.goal_par)] {
+            if let Some(par) = par {
+                let value = format!("{} {}", centiseconds(par.hard), centiseconds(par.medium));
+                parameters.insert(String::from(key), value);
+            }
+        }
+        if let Some(par) = item.coin_par {
+            parameters.insert(
+                String::from("coin_hs"),
+                format!("{} {}", par.hard, par.medium),
+            );
+        }
+
+        Ok(Self {
+            parameters,
+            brushes: item.brushes,
+        })
+    }
+}
+
+// Neverball's map compiler reads positions as whole numbers, so round them. Adding zero turns any
+// negative zeros positive.
+fn origin(point: DVec3) -> String {
+    let point = point.round() + DVec3::ZERO;
+    format!("{} {} {}", point.x, point.y, point.z)
+}
+
+fn point_entity<const N: usize>(parameters: [(&str, String); N]) -> QEntity {
+    QEntity {
+        parameters: parameters
+            .into_iter()
+            .map(|(key, value)| (String::from(key), value))
+            .collect(),
+        brushes: Vec::new(),
+    }
+}
+
+/// A coin, worth `value` points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coin {
+    pub origin: DVec3,
+    pub value: u32,
+}
+
+impl Coin {
+    pub fn new(origin: DVec3, value: u32) -> Self {
+        Self { origin, value }
+    }
+}
+
+impl From<Coin> for QEntity {
+    fn from(item: Coin) -> Self {
+        point_entity([
+            ("classname", String::from("light")),
+            ("origin", origin(item.origin)),
+            ("light", item.value.to_string()),
+        ])
+    }
+}
+
+/// The goal, which the ball must reach to finish the level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Goal {
+    pub origin: DVec3,
+}
+
+impl Goal {
+    pub fn new(origin: DVec3) -> Self {
+        Self { origin }
+    }
+}
+
+impl From<Goal> for QEntity {
+    fn from(item: Goal) -> Self {
+        point_entity([
+            ("classname", String::from("info_player_deathmatch")),
+            ("origin", origin(item.origin)),
+        ])
+    }
+}
+
+/// A jump, which teleports the ball to the [Target] named `target`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jump {
+    pub origin: DVec3,
+    pub target: String,
+}
+
+impl Jump {
+    pub fn new(origin: DVec3, target: impl Into<String>) -> Self {
+        Self {
+            origin,
+            target: target.into(),
+        }
+    }
+}
+
+impl From<Jump> for QEntity {
+    fn from(item: Jump) -> Self {
+        point_entity([
+            ("classname", String::from("target_teleporter")),
+            ("origin", origin(item.origin)),
+            ("target", item.target),
+        ])
+    }
+}
+
+/// A named point in space, used as the destination of a [Jump] or the focus of a [View].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    pub origin: DVec3,
+    pub name: String,
+}
+
+impl Target {
+    pub fn new(origin: DVec3, name: impl Into<String>) -> Self {
+        Self {
+            origin,
+            name: name.into(),
+        }
+    }
+}
+
+impl From<Target> for QEntity {
+    fn from(item: Target) -> Self {
+        point_entity([
+            ("classname", String::from("target_position")),
+            ("origin", origin(item.origin)),
+            ("targetname", item.name),
+        ])
+    }
+}
+
+/// A switch, which toggles the movers following the path named `target`.
+///
+/// When `timer` is non-zero, the switch flips back after that many seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Switch {
+    pub origin: DVec3,
+    pub target: String,
+    pub timer: f64,
+    pub invisible: bool,
+}
+
+impl Switch {
+    pub fn new(origin: DVec3, target: impl Into<String>, timer: f64) -> Self {
+        Self {
+            origin,
+            target: target.into(),
+            timer,
+            invisible: false,
+        }
+    }
+}
+
+impl From<Switch> for QEntity {
+    fn from(item: Switch) -> Self {
+        let mut entity = point_entity([
+            ("classname", String::from("info_camp")),
+            ("origin", origin(item.origin)),
+            ("target", item.target),
+            ("timer", item.timer.to_string()),
+        ]);
+        if item.invisible {
+            entity
+                .parameters
+                .insert(String::from("invisible"), String::from("1"));
+        }
+        entity
+    }
+}
+
+/// An item that makes the ball grow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grow {
+    pub origin: DVec3,
+}
+
+impl Grow {
+    pub fn new(origin: DVec3) -> Self {
+        Self { origin }
+    }
+}
+
+impl From<Grow> for QEntity {
+    fn from(item: Grow) -> Self {
+        point_entity([
+            ("classname", String::from("item_health_large")),
+            ("origin", origin(item.origin)),
+        ])
+    }
+}
+
+/// An item that makes the ball shrink.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shrink {
+    pub origin: DVec3,
+}
+
+impl Shrink {
+    pub fn new(origin: DVec3) -> Self {
+        Self { origin }
+    }
+}
+
+impl From<Shrink> for QEntity {
+    fn from(item: Shrink) -> Self {
+        point_entity([
+            ("classname", String::from("item_health_small")),
+            ("origin", origin(item.origin)),
+        ])
+    }
+}
+
+/// An item that adds `seconds` to the clock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clock {
+    pub origin: DVec3,
+    pub seconds: u32,
+}
+
+impl Clock {
+    pub fn new(origin: DVec3, seconds: u32) -> Self {
+        Self { origin, seconds }
+    }
+}
+
+impl From<Clock> for QEntity {
+    fn from(item: Clock) -> Self {
+        point_entity([
+            ("classname", String::from("item_clock")),
+            ("origin", origin(item.origin)),
+            ("light", item.seconds.to_string()),
+        ])
+    }
+}
+
+/// Where the ball starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BallStart {
+    pub origin: DVec3,
+}
+
+impl BallStart {
+    pub fn new(origin: DVec3) -> Self {
+        Self { origin }
+    }
+}
+
+impl From<BallStart> for QEntity {
+    fn from(item: BallStart) -> Self {
+        point_entity([
+            ("classname", String::from("info_player_start")),
+            ("origin", origin(item.origin)),
+        ])
+    }
+}
+
+/// The point the level is viewed from before play begins, looking at the [Target] named `target`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct View {
+    pub origin: DVec3,
+    pub target: String,
+}
+
+impl View {
+    pub fn new(origin: DVec3, target: impl Into<String>) -> Self {
+        Self {
+            origin,
+            target: target.into(),
+        }
+    }
+}
+
+impl From<View> for QEntity {
+    fn from(item: View) -> Self {
+        point_entity([
+            ("classname", String::from("info_player_intermission")),
+            ("origin", origin(item.origin)),
+            ("target", item.target),
+        ])
+    }
+}
+
+/// A set of brushes that moves along a chain of [PathCorner]s, starting at the corner named
+/// `target`.
+///
+/// Neverball moves the brushes by the position of the corner the train is at, so a train whose
+/// path starts at the origin begins where its brushes were built.
+#[derive(Debug, Clone)]
+pub struct Train {
+    pub target: String,
+    pub brushes: Vec<Brush>,
+}
+
+impl Train {
+    pub fn new(target: impl Into<String>, brushes: Vec<Brush>) -> Self {
+        Self {
+            target: target.into(),
+            brushes,
+        }
+    }
+}
+
+impl From<Train> for QEntity {
+    fn from(item: Train) -> Self {
+        QEntity {
+            brushes: item.brushes,
+            ..point_entity([
+                ("classname", String::from("func_train")),
+                ("target", item.target),
+            ])
+        }
+    }
+}
+
+/// One stop on the path of a [Train].
+///
+/// A train arriving here moves on to the corner named `target`, taking `speed` seconds to get
+/// there. With no `target`, the train stops. With `smooth` set, the train eases in and out of the
+/// corner rather than moving at a constant speed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathCorner {
+    pub origin: DVec3,
+    pub name: String,
+    pub target: Option<String>,
+    pub speed: f64,
+    pub smooth: bool,
+}
+
+impl PathCorner {
+    pub fn new(origin: DVec3, name: impl Into<String>, target: Option<String>, speed: f64) -> Self {
+        Self {
+            origin,
+            name: name.into(),
+            target,
+            speed,
+            smooth: false,
+        }
+    }
+}
+
+impl From<PathCorner> for QEntity {
+    fn from(item: PathCorner) -> Self {
+        let mut entity = point_entity([
+            ("classname", String::from("path_corner")),
+            ("origin", origin(item.origin)),
+            ("targetname", item.name),
+            ("speed", item.speed.to_string()),
+            ("smooth", String::from(if item.smooth { "1" } else { "0" })),
+        ]);
+        if let Some(target) = item.target {
+            entity.parameters.insert(String::from("target"), target);
+        }
+        entity
+    }
+}
+
+/// A TrenchBroom group, letting the brushes inside be selected and moved as one object.
+///
+/// Groups must have an `id` unique among the groups and layers in the map. A group belongs to the
+/// default layer unless `layer` names the id of a [TrenchbroomLayer].
+#[derive(Debug, Clone)]
+pub struct TrenchbroomGroup {
+    pub name: String,
+    pub id: u32,
+    pub layer: Option<u32>,
+    pub brushes: Vec<Brush>,
+}
+
+impl TrenchbroomGroup {
+    pub fn new(name: impl Into<String>, id: u32, brushes: Vec<Brush>) -> Self {
+        Self {
+            name: name.into(),
+            id,
+            layer: None,
+            brushes,
+        }
+    }
+}
+
+impl From<TrenchbroomGroup> for QEntity {
+    fn from(item: TrenchbroomGroup) -> Self {
+        let mut parameters = HashMap::new();
+        parameters.insert(String::from("classname"), String::from("func_group"));
+        parameters.insert(String::from("_tb_type"), String::from("_tb_group"));
+        parameters.insert(String::from("_tb_name"), item.name);
+        parameters.insert(String::from("_tb_id"), item.id.to_string());
+        if let Some(layer) = item.layer {
+            parameters.insert(String::from("_tb_layer"), layer.to_string());
+        }
+        Self {
+            parameters,
+            brushes: item.brushes,
+        }
+    }
+}
+
+/// A TrenchBroom layer, which can be hidden or locked as a whole.
+///
+/// Layers must have an `id` unique among the groups and layers in the map.
+#[derive(Debug, Clone)]
+pub struct TrenchbroomLayer {
+    pub name: String,
+    pub id: u32,
+    pub sort_index: Option<u32>,
+    pub brushes: Vec<Brush>,
+}
+
+impl TrenchbroomLayer {
+    pub fn new(name: impl Into<String>, id: u32, brushes: Vec<Brush>) -> Self {
+        Self {
+            name: name.into(),
+            id,
+            sort_index: None,
+            brushes,
+        }
+    }
+}
+
+impl From<TrenchbroomLayer> for QEntity {
+    fn from(item: TrenchbroomLayer) -> Self {
+        let mut parameters = HashMap::new();
+        parameters.insert(String::from("classname"), String::from("func_group"));
+        parameters.insert(String::from("_tb_type"), String::from("_tb_layer"));
+        parameters.insert(String::from("_tb_name"), item.name);
+        parameters.insert(String::from("_tb_id"), item.id.to_string());
+        if let Some(sort_index) = item.sort_index {
+            parameters.insert(String::from("_tb_layer_sort_index"), sort_index.to_string());
+        }
+        Self {
+            parameters,
+            brushes: item.brushes,
+        }
+    }
+}
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    fn param<'a>(entity: &'a QEntity, key: &str) -> &'a str {
+        entity.parameters.get(key).unwrap()
+    }
+
+    #[test]
+    fn coin() {
+        let entity = QEntity::from(Coin::new(DVec3::new(64.2, -0.3, 15.6), 5));
+        assert_eq!(entity.parameters.len(), 3);
+        assert_eq!(param(&entity, "classname"), "light");
+        assert_eq!(param(&entity, "origin"), "64 0 16");
+        assert_eq!(param(&entity, "light"), "5");
+        assert!(entity.brushes.is_empty());
+    }
+
+    #[test]
+    fn jump_and_target() {
+        let jump = QEntity::from(Jump::new(DVec3::ZERO, "landing"));
+        let target = QEntity::from(Target::new(DVec3::new(0.0, 256.0, 64.0), "landing"));
+        assert_eq!(param(&jump, "classname"), "target_teleporter");
+        assert_eq!(param(&target, "classname"), "target_position");
+        assert_eq!(param(&jump, "target"), param(&target, "targetname"));
+        assert_eq!(param(&target, "origin"), "0 256 64");
+    }
+
+    #[test]
+    fn switch() {
+        let mut switch = Switch::new(DVec3::ZERO, "platform", 2.5);
+        let entity = QEntity::from(switch.clone());
+        assert_eq!(param(&entity, "classname"), "info_camp");
+        assert_eq!(param(&entity, "timer"), "2.5");
+        assert!(!entity.parameters.contains_key("invisible"));
+
+        switch.invisible = true;
+        let entity = QEntity::from(switch);
+        assert_eq!(param(&entity, "invisible"), "1");
+    }
+
+    #[test]
+    fn worldspawn() {
+        let mut worldspawn = Worldspawn {
+            message: Some(String::from("Collect the coins\\\\and find the goal.")),
+            song: Some(String::from("bgm/track2.ogg")),
+            time: Some(Duration::from_secs(60)),
+            goal: 10,
+            time_par: Some(ParScores::new(
+                Duration::from_secs(15),
+                Duration::from_millis(22_500),
+            )),
+            coin_par: Some(ParScores::new(20, 15)),
+            ..Worldspawn::new(Vec::new())
+        };
+        let entity = QEntity::try_from(worldspawn.clone()).unwrap();
+        assert_eq!(param(&entity, "classname"), "worldspawn");
+        assert_eq!(
+            param(&entity, "message"),
+            "Collect the coins\\\\and find the goal."
+        );
+        assert_eq!(param(&entity, "time"), "6000");
+        assert_eq!(param(&entity, "goal"), "10");
+        assert_eq!(param(&entity, "time_hs"), "1500 2250");
+        assert_eq!(param(&entity, "coin_hs"), "20 15");
+        assert!(!entity.parameters.contains_key("back"));
+        assert!(!entity.parameters.contains_key("goal_hs"));
+
+        worldspawn.goal_par = Some(ParScores::new(
+            Duration::from_secs(50),
+            Duration::from_secs(70),
+        ));
+        assert_eq!(
+            QEntity::try_from(worldspawn.clone()).unwrap_err(),
+            WorldspawnError::ParExceedsTime { key: "goal_hs" }
+        );
+        worldspawn.goal_par = None;
+
+        worldspawn.coin_par = Some(ParScores::new(10, 15));
+        assert_eq!(
+            worldspawn.validate(),
+            Err(WorldspawnError::ParOrder { key: "coin_hs" })
+        );
+        worldspawn.coin_par = None;
+
+        worldspawn.song = Some(String::from("bgm/\"track\".ogg"));
+        assert_eq!(
+            worldspawn.validate(),
+            Err(WorldspawnError::InvalidText { key: "song" })
+        );
+    }
+
+    #[test]
+    fn trenchbroom_group() {
+        let mut group = TrenchbroomGroup::new("Curve", 3, Vec::new());
+        let entity = QEntity::from(group.clone());
+        assert_eq!(param(&entity, "classname"), "func_group");
+        assert_eq!(param(&entity, "_tb_type"), "_tb_group");
+        assert_eq!(param(&entity, "_tb_name"), "Curve");
+        assert_eq!(param(&entity, "_tb_id"), "3");
+        assert!(!entity.parameters.contains_key("_tb_layer"));
+
+        group.layer = Some(1);
+        let entity = QEntity::from(group);
+        assert_eq!(param(&entity, "_tb_layer"), "1");
+
+        let entity = QEntity::from(TrenchbroomLayer::new("Curves", 1, Vec::new()));
+        assert_eq!(param(&entity, "_tb_type"), "_tb_layer");
+    }
+}
@@ -5,9 +5,13 @@
 
 const TEX_DEFAULT: &str = "mtrl/invisible";
 const ALMOST_EQUAL_DELTA: f64 = 0.000000001;
+use crate::map::hull::{HullError, MAX_HULL_ITER, convex_hull, prism};
+use crate::map::material::SurfaceRole;
+use crate::map::writer::{MapFormat, MapWriter};
 use core::fmt;
-use glam::DVec3;
+use glam::{DQuat, DVec3};
 use std::fmt::{Display, Formatter};
+use thiserror::Error;
 
 /// A face represented as three points in space.
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
@@ -21,6 +25,12 @@ impl SideGeom {
         let Self([_p0, p1, _p2]) = self;
         Some(self.normal()?.dot(p1))
     }
+    pub fn plane(self) -> Option<Plane> {
+        Some(Plane {
+            normal: self.normal()?,
+            dist: self.dist()?,
+        })
+    }
     pub fn equivalent(self, other: SideGeom) -> bool {
         let Some(normal) = self.normal() else {
             return false;
@@ -45,21 +55,256 @@ impl SideGeom {
     }
 }
 
-// TODO: Add texture offset, scale, rotation
+/// An infinite plane, described by a unit normal and its distance from the origin along that
+/// normal.
+///
+/// The plane bounds the half-space behind it, i.e. every point `p` where `normal.dot(p) <= dist`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: DVec3,
+    pub dist: f64,
+}
+
+impl Plane {
+    /// Create a plane from a normal of any non-zero length. The normal is normalized and `dist`
+    /// is scaled to match.
+    pub fn new(normal: DVec3, dist: f64) -> Option<Self> {
+        let length = normal.length();
+        Some(Self {
+            normal: normal.try_normalize()?,
+            dist: dist / length,
+        })
+    }
+    /// The signed distance from the plane to a point; positive in front of the plane.
+    pub fn distance_to(self, point: DVec3) -> f64 {
+        self.normal.dot(point) - self.dist
+    }
+    pub fn equivalent(self, other: Plane) -> bool {
+        self.normal.dot(other.normal) >= 1.0 - ALMOST_EQUAL_DELTA
+            && (self.dist - other.dist).abs() <= PLANE_EPSILON
+    }
+    /// The same plane facing the other way, bounding the opposite half-space.
+    pub fn flipped(self) -> Self {
+        Self {
+            normal: -self.normal,
+            dist: -self.dist,
+        }
+    }
+}
 
-/// A struct defining the texture of a face.
+/// A struct defining the texture of a face and how that texture is aligned.
+///
+/// These are the values written after the texture name in a Quake3 map: the texture's offset,
+/// rotation (in degrees), and scale, followed by the content flags, surface flags, and value.
 ///
-/// This struct is missing important properties, like the texture offset, scale, and rotation. It
-/// is currently unused.
-#[derive(Debug, Clone, PartialEq)]
+/// `role` is not written to the map. It records which part of a curve the face belongs to, so a
+/// [MaterialMap](crate::map::material::MaterialMap) can assign materials after the curve is built.
+/// Since it does not change how a side looks, it is ignored when comparing materials.
+///
+/// `unaligned` is not written either. It keeps the alignment a side had before
+/// [align_textures](crate::curve::extrude::texture::align_textures) turned it to follow a path, so
+/// aligning it again starts from the same place. It is also ignored when comparing materials.
+#[derive(Debug, Clone)]
 pub struct SideMtrl {
     pub texture: String,
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub rotation: f64,
+    pub scale_x: f64,
+    pub scale_y: f64,
+    pub content_flags: u32,
+    pub surface_flags: u32,
+    pub value: i32,
+    pub role: Option<SurfaceRole>,
+    pub unaligned: Option<TextureAlignment>,
+}
+
+/// The offset, rotation, and scale of a texture on a side.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TextureAlignment {
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub rotation: f64,
+    pub scale_x: f64,
+    pub scale_y: f64,
+}
+
+impl SideMtrl {
+    /// A material with the given texture and default alignment.
+    pub fn new(texture: impl Into<String>) -> Self {
+        Self {
+            texture: texture.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The material's current texture alignment.
+    pub fn alignment(&self) -> TextureAlignment {
+        TextureAlignment {
+            offset_x: self.offset_x,
+            offset_y: self.offset_y,
+            rotation: self.rotation,
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+        }
+    }
+
+    /// The texture's `s` and `t` axes on a face with the given normal, after rotation and scale.
+    ///
+    /// A point `p` on the face maps to the texture coordinates `(p.dot(s) + offset_x,
+    /// p.dot(t) + offset_y)`, measured in texels.
+    pub fn texture_axes(&self, normal: DVec3) -> [DVec3; 2] {
+        let [s, t] = rotate_texture_axes(base_texture_axes(normal), self.rotation);
+        [s / self.scale_x, t / self.scale_y]
+    }
+
+    /// The texture's `u` and `v` axes on a face with the given normal, for formats that store
+    /// them. Unlike [texture_axes](Self::texture_axes), these lie in the face's plane, so the
+    /// texture is not stretched on faces at an angle. They are rotated but not scaled.
+    ///
+    /// On faces parallel to an axial plane, these match the standard format's axes.
+    pub fn face_texture_axes(&self, normal: DVec3) -> [DVec3; 2] {
+        let [s, t] = base_texture_axes(normal);
+        let u = s.reject_from_normalized(normal).normalize();
+        // Keep v on the same side as Quake3's t axis, so axial faces are not flipped.
+        let v = normal.cross(u).normalize();
+        let v = if v.dot(t) < 0.0 { -v } else { v };
+        let rotation = DQuat::from_axis_angle(v.cross(u), self.rotation.to_radians());
+        [rotation * u, rotation * v]
+    }
+}
+
+// Doom3 measures textures in fractions of their size, which depends on the image. Use the scale
+// its editor gives new brushes, which suits a 128 by 128 texture.
+const BRUSH_DEF3_SCALE: f64 = 0.0078125;
+
+// How far the cosine between a side's normal and its normal after rounding to whole units may
+// fall below 1, which allows a tilt of about two and a half degrees.
+const ROUNDED_NORMAL_TOLERANCE: f64 = 0.001;
+
+// The axes Doom3 measures a brushDef3 texture matrix against, found from the face normal alone.
+fn brush_def3_axes(normal: DVec3) -> [DVec3; 2] {
+    let normal = normal.map(|c| if c.abs() < PLANE_EPSILON { 0.0 } else { c });
+    let horizontal = normal.x.hypot(normal.y);
+    let (sin_z, cos_z) = if horizontal > 0.0 {
+        (normal.y / horizontal, normal.x / horizontal)
+    } else {
+        (0.0, 1.0)
+    };
+    [
+        DVec3::new(-sin_z, cos_z, 0.0),
+        DVec3::new(normal.z * cos_z, normal.z * sin_z, -horizontal),
+    ]
+}
+
+// Quake3 projects a texture onto a face from whichever axial plane is closest to the face. Each
+// entry is that plane's normal, followed by the texture's s and t axes.
+const BASE_TEXTURE_AXES: [[DVec3; 3]; 6] = [
+    [
+        DVec3::new(0.0, 0.0, 1.0),
+        DVec3::new(1.0, 0.0, 0.0),
+        DVec3::new(0.0, -1.0, 0.0),
+    ],
+    [
+        DVec3::new(0.0, 0.0, -1.0),
+        DVec3::new(1.0, 0.0, 0.0),
+        DVec3::new(0.0, -1.0, 0.0),
+    ],
+    [
+        DVec3::new(1.0, 0.0, 0.0),
+        DVec3::new(0.0, 1.0, 0.0),
+        DVec3::new(0.0, 0.0, -1.0),
+    ],
+    [
+        DVec3::new(-1.0, 0.0, 0.0),
+        DVec3::new(0.0, 1.0, 0.0),
+        DVec3::new(0.0, 0.0, -1.0),
+    ],
+    [
+        DVec3::new(0.0, 1.0, 0.0),
+        DVec3::new(1.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, -1.0),
+    ],
+    [
+        DVec3::new(0.0, -1.0, 0.0),
+        DVec3::new(1.0, 0.0, 0.0),
+        DVec3::new(0.0, 0.0, -1.0),
+    ],
+];
+
+/// The unrotated, unscaled texture axes Quake3 uses for a face with the given normal.
+pub(crate) fn base_texture_axes(normal: DVec3) -> [DVec3; 2] {
+    let mut best = 0.0;
+    let mut best_axes = [BASE_TEXTURE_AXES[0][1], BASE_TEXTURE_AXES[0][2]];
+    for [axis, s, t] in BASE_TEXTURE_AXES {
+        let dot = normal.dot(axis);
+        if dot > best {
+            best = dot;
+            best_axes = [s, t];
+        }
+    }
+    best_axes
+}
+
+/// The two components (x, y, or z) the base texture axes lie in. Rotation happens in this plane.
+pub(crate) fn texture_plane_components([s, t]: [DVec3; 2]) -> (usize, usize) {
+    let component = |v: DVec3| v.to_array().iter().position(|c| *c != 0.0).unwrap_or(0);
+    (component(s), component(t))
+}
+
+// Rotate the base texture axes by an angle in degrees, the same way Quake3 tools do.
+fn rotate_texture_axes(axes: [DVec3; 2], rotation: f64) -> [DVec3; 2] {
+    let (sv, tv) = texture_plane_components(axes);
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    axes.map(|axis| {
+        let mut rotated = axis;
+        rotated[sv] = cos * axis[sv] - sin * axis[tv];
+        rotated[tv] = sin * axis[sv] + cos * axis[tv];
+        rotated
+    })
+}
+
+impl PartialEq for SideMtrl {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            texture,
+            offset_x,
+            offset_y,
+            rotation,
+            scale_x,
+            scale_y,
+            content_flags,
+            surface_flags,
+            value,
+            role: _,
+            unaligned: _,
+        } = self;
+        *texture == other.texture
+            && *offset_x == other.offset_x
+            && *offset_y == other.offset_y
+            && *rotation == other.rotation
+            && *scale_x == other.scale_x
+            && *scale_y == other.scale_y
+            && *content_flags == other.content_flags
+            && *surface_flags == other.surface_flags
+            && *value == other.value
+    }
 }
 
 impl Default for SideMtrl {
     fn default() -> Self {
         Self {
             texture: String::from(TEX_DEFAULT),
+            offset_x: 0.0,
+            offset_y: 0.0,
+            rotation: 0.0,
+            scale_x: 0.5,
+            scale_y: 0.5,
+            content_flags: 0,
+            surface_flags: 0,
+            value: 0,
+            role: None,
+            unaligned: None,
         }
     }
 }
@@ -72,52 +317,129 @@ pub struct Side {
 }
 
 impl Side {
-    pub(crate) fn bake(&self) -> impl Display + use<'_> {
-        struct SideDisplay<'a>(&'a Side);
+    pub(crate) fn bake(&self, writer: MapWriter) -> impl Display + use<'_> {
+        struct SideDisplay<'a>(&'a Side, MapWriter);
         impl Display for SideDisplay<'_> {
             fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+                let (side, writer) = (self.0, self.1);
+                let mtrl = &side.mtrl;
+
+                if writer.format == MapFormat::BrushDef3 {
+                    let precision = writer.precision;
+                    let Plane { normal, dist } = side.geom.plane().unwrap_or_default();
+                    // Express the texture's axes, scale and offset against Doom3's own axes.
+                    let [u, v] = mtrl.face_texture_axes(normal);
+                    let [s, t] = brush_def3_axes(normal);
+                    let row = |axis: DVec3, scale: f64, offset: f64| {
+                        [axis.dot(s) / scale, axis.dot(t) / scale, offset]
+                            .map(|c| c * BRUSH_DEF3_SCALE + 0.0)
+                    };
+                    let [u0, u1, u2] = row(u, mtrl.scale_x, mtrl.offset_x);
+                    let [v0, v1, v2] = row(v, mtrl.scale_y, mtrl.offset_y);
+                    // Doom3 stores a plane as `normal.dot(p) + d = 0`.
+                    return write!(
+                        f,
+                        "( {:.precision$} {:.precision$} {:.precision$} {:.precision$} ) ( ( {u0} {u1} {u2} ) ( {v0} {v1} {v2} ) ) \"{}\" {} {} {}",
+                        normal.x,
+                        normal.y,
+                        normal.z,
+                        -dist + 0.0,
+                        mtrl.texture,
+                        mtrl.content_flags,
+                        mtrl.surface_flags,
+                        mtrl.value,
+                    );
+                }
+
+                let rounded = SideGeom(side.geom.0.map(|point| point.round() + DVec3::ZERO));
+                // Rounding a thin side can tilt or flip its plane, so fall back to full
+                // precision unless the rounded plane still faces the same way.
+                let keeps_facing = match (rounded.normal(), side.geom.normal()) {
+                    (Some(a), Some(b)) => a.dot(b) > 1.0 - ROUNDED_NORMAL_TOLERANCE,
+                    _ => false,
+                };
+                let (points, precision) = if writer.integer_coordinates && keeps_facing {
+                    (rounded.0, 0)
+                } else {
+                    (side.geom.0, writer.precision)
+                };
+                for point in points {
+                    write!(
+                        f,
+                        "( {:.precision$} {:.precision$} {:.precision$} ) ",
+                        point[0], point[1], point[2]
+                    )?;
+                }
+                write!(f, "{} ", mtrl.texture)?;
+                if writer.format == MapFormat::Valve220 {
+                    let normal = side.geom.normal().unwrap_or(DVec3::Z);
+                    let [u, v] = mtrl
+                        .face_texture_axes(normal)
+                        .map(|axis| axis + DVec3::ZERO);
+                    write!(
+                        f,
+                        "[ {} {} {} {} ] [ {} {} {} {} ] ",
+                        u.x, u.y, u.z, mtrl.offset_x, v.x, v.y, v.z, mtrl.offset_y,
+                    )?;
+                } else {
+                    write!(f, "{} {} ", mtrl.offset_x, mtrl.offset_y)?;
+                }
                 write!(
                     f,
-                    "( {:.6} {:.6} {:.6} ) ( {:.6} {:.6} {:.6} ) ( {:.6} {:.6} {:.6} ) {} 0 0 0 0.5 0.5 0 0 0",
-                    self.0.geom.0[0][0],
-                    self.0.geom.0[0][1],
-                    self.0.geom.0[0][2],
-                    self.0.geom.0[1][0],
-                    self.0.geom.0[1][1],
-                    self.0.geom.0[1][2],
-                    self.0.geom.0[2][0],
-                    self.0.geom.0[2][1],
-                    self.0.geom.0[2][2],
-                    self.0.mtrl.texture
+                    "{} {} {} {} {} {}",
+                    mtrl.rotation,
+                    mtrl.scale_x,
+                    mtrl.scale_y,
+                    mtrl.content_flags,
+                    mtrl.surface_flags,
+                    mtrl.value,
                 )
             }
         }
-        SideDisplay(self)
+        SideDisplay(self, writer)
     }
 }
 
-use chull::ConvexHullWrapper;
 /// A brush, representing a convex polyhedron that can be instantiated in a Neverball level. Curves
 /// consist of multiple Brushes.
 #[derive(Debug, Clone)]
 pub struct Brush {
-    vertices: Vec<DVec3>,
-    sides: Vec<([usize; 3], SideMtrl)>, // the [usize; 3] contains indices into the vertices vector
+    pub(crate) vertices: Vec<DVec3>,
+    pub(crate) sides: Vec<([usize; 3], SideMtrl)>, // the [usize; 3] contains indices into the vertices vector
 }
 
 impl Brush {
+    /// Build a brush from the convex hull of a set of points. Points inside the hull are ignored,
+    /// and triangles on the same plane are merged into one side.
+    ///
+    /// `max_iter` limits how many points are added to the hull before giving up.
     pub fn try_from_vertices(
         vertices: &[DVec3],
         max_iter: Option<usize>,
-    ) -> Result<Self, chull::convex::ErrorKind> {
-        let vertices: Vec<Vec<f64>> = vertices
-            .iter()
-            .map(|vertex| vec![vertex.x, vertex.y, vertex.z])
-            .collect();
-
-        let hull = ConvexHullWrapper::try_new(&vertices, max_iter)?;
+    ) -> Result<Self, HullError> {
+        convex_hull(vertices, max_iter)
+    }
 
-        Ok(hull.into())
+    /// Build a brush spanning two rings of points, such as the two ends of a segment of an
+    /// extrusion. The rings must have the same number of points, in the same order around the
+    /// profile.
+    ///
+    /// Prisms and frusta, whose sides are all flat and whose edges are all convex, are built
+    /// directly, with one side for each pair of neighbouring points followed by the two ends.
+    /// Anything else, such as a twisted segment, falls back to
+    /// [try_from_vertices](Self::try_from_vertices).
+    pub fn try_from_rings(
+        ring0: &[DVec3],
+        ring1: &[DVec3],
+        max_iter: Option<usize>,
+    ) -> Result<Self, HullError> {
+        match prism(ring0, ring1) {
+            Some(brush) => Ok(brush),
+            None => {
+                let vertices: Vec<DVec3> = ring0.iter().chain(ring1).copied().collect();
+                convex_hull(&vertices, max_iter)
+            }
+        }
     }
 
     pub fn side_vertex_indices(&self) -> (&Vec<DVec3>, &Vec<([usize; 3], SideMtrl)>) {
@@ -158,58 +480,562 @@ impl Brush {
     pub fn vertices(&self) -> &Vec<DVec3> {
         &self.vertices
     }
-}
 
-#[allow(clippy::get_first)]
-impl From<ConvexHullWrapper<f64>> for Brush {
-    fn from(hull: ConvexHullWrapper<f64>) -> Self {
-        let (vertices, side_indices) = hull.vertices_indices();
-
-        let vertices: Vec<DVec3> = vertices
-            .into_iter()
-            .map(|vertex| DVec3 {
-                x: *vertex
-                    .get(0)
-                    .expect("vertices expected to have three components"),
-                y: *vertex
-                    .get(1)
-                    .expect("vertices expected to have three components"),
-                z: *vertex
-                    .get(2)
-                    .expect("vertices expected to have three components"),
+    pub fn materials_mut(&mut self) -> impl Iterator<Item = &mut SideMtrl> + use<'_> {
+        self.sides.iter_mut().map(|(_, mtrl)| mtrl)
+    }
+
+    /// Update the material of every side, given the side's geometry.
+    pub(crate) fn update_materials(&mut self, mut update: impl FnMut(SideGeom, &mut SideMtrl)) {
+        for ([i0, i1, i2], mtrl) in self.sides.iter_mut() {
+            let geom = SideGeom([self.vertices[*i0], self.vertices[*i1], self.vertices[*i2]]);
+            update(geom, mtrl);
+        }
+    }
+
+    /// Build a brush from a set of planes by intersecting the half-spaces behind each plane.
+    ///
+    /// Planes that do not touch the resulting brush are dropped. Every side receives the default
+    /// material.
+    pub fn try_from_planes(planes: &[Plane]) -> Result<Self, BrushError> {
+        Self::from_planes_mtrl(
+            planes
+                .iter()
+                .map(|plane| (*plane, SideMtrl::default()))
+                .collect(),
+        )
+    }
+
+    /// Build a brush from a set of sides by intersecting the half-spaces behind each side.
+    ///
+    /// Sides that do not touch the resulting brush are dropped. The remaining sides keep their
+    /// materials.
+    pub fn try_from_sides(sides: &[Side]) -> Result<Self, BrushError> {
+        let planes = sides
+            .iter()
+            .enumerate()
+            .map(|(index, side)| match side.geom.plane() {
+                Some(plane) => Ok((plane, side.mtrl.clone())),
+                None => Err(BrushError::DegenerateSide { index }),
             })
+            .collect::<Result<_, _>>()?;
+        Self::from_planes_mtrl(planes)
+    }
+
+    /// Cut the brush in two along a plane, returning the parts behind and in front of the plane.
+    ///
+    /// A part is `None` if the brush lies entirely on the other side of the plane. Both parts keep
+    /// the materials of the original sides, and the new side made by the cut receives the default
+    /// material.
+    ///
+    /// A part too thin to be built lies within rounding distance of the plane, so the brush is then
+    /// returned whole on the other side. If neither part can be built, the error is returned.
+    pub fn split(&self, plane: Plane) -> Result<(Option<Brush>, Option<Brush>), BrushError> {
+        let mtrl = SideMtrl::default();
+        self.split_mtrl(plane, &mtrl, &mtrl)
+    }
+
+    /// Like [split](Self::split), with the given materials on the new sides of the two parts.
+    fn split_mtrl(
+        &self,
+        plane: Plane,
+        back_mtrl: &SideMtrl,
+        front_mtrl: &SideMtrl,
+    ) -> Result<(Option<Brush>, Option<Brush>), BrushError> {
+        let distances: Vec<f64> = self
+            .vertices
+            .iter()
+            .map(|vertex| plane.distance_to(*vertex))
             .collect();
+        if distances.iter().all(|&distance| distance <= PLANE_EPSILON) {
+            return Ok((Some(self.clone()), None));
+        }
+        if distances.iter().all(|&distance| distance >= -PLANE_EPSILON) {
+            return Ok((None, Some(self.clone())));
+        }
+
+        // Classify every vertex once, so both parts agree on which points lie on the plane. Points
+        // on the plane go to both parts, as do the points where edges cross it.
+        let mut back: Vec<DVec3> = Vec::new();
+        let mut front: Vec<DVec3> = Vec::new();
+        for (vertex, &distance) in self.vertices.iter().zip(&distances) {
+            if distance <= PLANE_EPSILON {
+                back.push(*vertex);
+            }
+            if distance >= -PLANE_EPSILON {
+                front.push(*vertex);
+            }
+        }
+        for ([i0, i1, i2], _) in &self.sides {
+            for (i, j) in [(*i0, *i1), (*i1, *i2), (*i2, *i0)] {
+                let (d0, d1) = (distances[i], distances[j]);
+                if (d0 < -PLANE_EPSILON && d1 > PLANE_EPSILON)
+                    || (d0 > PLANE_EPSILON && d1 < -PLANE_EPSILON)
+                {
+                    let point = self.vertices[i].lerp(self.vertices[j], d0 / (d0 - d1));
+                    back.push(point);
+                    front.push(point);
+                }
+            }
+        }
+
+        let originals: Vec<Side> = self.to_sides_unique();
+        let part = |points: &[DVec3], cut: Plane, mtrl: &SideMtrl| {
+            let mut part = convex_hull(points, MAX_HULL_ITER)?;
+            part.update_materials(|geom, part_mtrl| {
+                let on = |plane: Plane| {
+                    geom.normal()
+                        .is_some_and(|normal| normal.dot(plane.normal) > 0.0)
+                        && geom
+                            .0
+                            .iter()
+                            .all(|point| plane.distance_to(*point).abs() <= PLANE_EPSILON)
+                };
+                if on(cut) {
+                    *part_mtrl = mtrl.clone();
+                } else if let Some(side) = originals
+                    .iter()
+                    .find(|side| side.geom.plane().is_some_and(on))
+                {
+                    *part_mtrl = side.mtrl.clone();
+                }
+            });
+            Ok::<_, HullError>(part)
+        };
+        match (
+            part(&back, plane, back_mtrl),
+            part(&front, plane.flipped(), front_mtrl),
+        ) {
+            (Ok(back), Ok(front)) => Ok((Some(back), Some(front))),
+            (Ok(_), Err(_)) => Ok((Some(self.clone()), None)),
+            (Err(_), Ok(_)) => Ok((None, Some(self.clone()))),
+            (Err(error), Err(_)) => Err(error.into()),
+        }
+    }
+
+    /// Carve another brush out of this one, returning convex pieces that together cover the part
+    /// of this brush outside `other`.
+    ///
+    /// As in Quake's CSG subtract, the brush is split along each side of `other` in turn, and the
+    /// pieces in front of a side are kept. The sides of the pieces facing into the hole take the
+    /// materials of the sides of `other` that made them, and sides between pieces receive the
+    /// default material. If the brushes do not overlap, the brush is returned whole. Fails if a
+    /// piece cannot be built, rather than leave part of the hole uncut.
+    pub fn subtract(&self, other: &Brush) -> Result<Vec<Brush>, BrushError> {
+        let mut pieces = Vec::new();
+        let mut inside = self.clone();
+        for side in other.to_sides_unique() {
+            let Some(plane) = side.geom.plane() else {
+                continue;
+            };
+            let (back, front) = inside.split_mtrl(plane, &SideMtrl::default(), &side.mtrl)?;
+            pieces.extend(front);
+            match back {
+                Some(back) => inside = back,
+                // The brush lies entirely in front of this side, so nothing is cut away.
+                None => return Ok(vec![self.clone()]),
+            }
+        }
+        Ok(pieces)
+    }
+
+    fn from_planes_mtrl(planes: Vec<(Plane, SideMtrl)>) -> Result<Self, BrushError> {
+        let mut vertices: Vec<DVec3> = Vec::new();
+        let mut sides = Vec::new();
+        let mut face_count = 0;
+
+        for (i, (plane, mtrl)) in planes.iter().enumerate() {
+            let mut winding = base_winding(*plane);
+            for (j, (other, _)) in planes.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                // Identical planes would never clip each other away; keep only the first.
+                if j < i && plane.equivalent(*other) {
+                    winding.clear();
+                    break;
+                }
+                winding = clip_winding(&winding, *other);
+                if winding.is_empty() {
+                    break;
+                }
+            }
+            if winding.len() < 3 {
+                continue;
+            }
+            if winding
+                .iter()
+                .any(|point| point.abs().max_element() > WINDING_EXTENT / 2.0)
+            {
+                // The winding reached the edge of the world, so the planes leave the brush open.
+                return Err(BrushError::Unbounded);
+            }
+
+            let indices: Vec<usize> = winding
+                .iter()
+                .map(|point| {
+                    match vertices
+                        .iter()
+                        .position(|vertex| vertex.distance(*point) < PLANE_EPSILON)
+                    {
+                        Some(index) => index,
+                        None => {
+                            vertices.push(*point);
+                            vertices.len() - 1
+                        }
+                    }
+                })
+                .collect();
+
+            let sides_before = sides.len();
+            for k in 1..indices.len() - 1 {
+                let [i0, i1, i2] = [indices[0], indices[k], indices[k + 1]];
+                let triangle = SideGeom([vertices[i0], vertices[i1], vertices[i2]]);
+                let Some(triangle_normal) = triangle.normal() else {
+                    continue;
+                };
+                let order = if triangle_normal.dot(plane.normal) > 0.0 {
+                    [i0, i1, i2]
+                } else {
+                    [i0, i2, i1]
+                };
+                sides.push((order, mtrl.clone()));
+            }
+            if sides.len() > sides_before {
+                face_count += 1;
+            }
+        }
+
+        // Anything with fewer than four faces is flat, or nothing at all.
+        if face_count < 4 {
+            return Err(BrushError::Empty);
+        }
+
+        // Degenerate windings may have left behind vertices no side refers to.
+        let mut remap = vec![None; vertices.len()];
+        let mut used_vertices = Vec::new();
+        for (indices, _) in sides.iter_mut() {
+            for index in indices.iter_mut() {
+                *index = *remap[*index].get_or_insert_with(|| {
+                    used_vertices.push(vertices[*index]);
+                    used_vertices.len() - 1
+                });
+            }
+        }
+
+        Ok(Self {
+            vertices: used_vertices,
+            sides,
+        })
+    }
+}
+
+/// Cut every brush along a plane, returning the parts behind the plane and the parts in front of
+/// it. Brushes that lie entirely on one side are kept whole.
+pub fn split_brushes(
+    brushes: &[Brush],
+    plane: Plane,
+) -> Result<(Vec<Brush>, Vec<Brush>), BrushError> {
+    let mut back = Vec::new();
+    let mut front = Vec::new();
+    for brush in brushes {
+        let (b, f) = brush.split(plane)?;
+        back.extend(b);
+        front.extend(f);
+    }
+    Ok((back, front))
+}
+
+/// Carve every brush in `cutters` out of every brush in `brushes`, as by [Brush::subtract].
+pub fn subtract_brushes(brushes: &[Brush], cutters: &[Brush]) -> Result<Vec<Brush>, BrushError> {
+    let mut brushes = brushes.to_vec();
+    for cutter in cutters {
+        let mut pieces = Vec::new();
+        for brush in &brushes {
+            pieces.extend(brush.subtract(cutter)?);
+        }
+        brushes = pieces;
+    }
+    Ok(brushes)
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum BrushError {
+    #[error("Side {index} does not define a plane; its three points are collinear.")]
+    DegenerateSide { index: usize },
+    #[error("The planes do not enclose any volume.")]
+    Empty,
+    #[error("The planes do not enclose a finite volume.")]
+    Unbounded,
+    #[error("Failed to find convex hull: {0}")]
+    ConvexHullFail(#[from] HullError),
+}
+
+// Half the width of the initial winding built on each plane. Anything beyond this is considered
+// outside the world.
+const WINDING_EXTENT: f64 = 1_000_000.0;
+const PLANE_EPSILON: f64 = 0.000001;
+
+// A very large square lying on the given plane.
+fn base_winding(Plane { normal, dist }: Plane) -> Vec<DVec3> {
+    let up = if normal.z.abs() > normal.x.abs() && normal.z.abs() > normal.y.abs() {
+        DVec3::X
+    } else {
+        DVec3::Z
+    };
+    let up = (up - normal * up.dot(normal)).normalize() * WINDING_EXTENT;
+    let right = up.cross(normal);
+    let origin = normal * dist;
+    vec![
+        origin - right + up,
+        origin + right + up,
+        origin + right - up,
+        origin - right - up,
+    ]
+}
+
+// Clip a convex polygon, keeping only the part behind the given plane.
+fn clip_winding(winding: &[DVec3], plane: Plane) -> Vec<DVec3> {
+    let dists: Vec<f64> = winding
+        .iter()
+        .map(|point| plane.distance_to(*point))
+        .collect();
+    if dists.iter().all(|d| *d <= PLANE_EPSILON) {
+        return winding.to_vec();
+    }
+    if dists.iter().all(|d| *d >= -PLANE_EPSILON) {
+        return Vec::new();
+    }
+
+    let mut result = Vec::new();
+    for i in 0..winding.len() {
+        let j = (i + 1) % winding.len();
+        let (p0, p1) = (winding[i], winding[j]);
+        let (d0, d1) = (dists[i], dists[j]);
+        if d0 <= PLANE_EPSILON {
+            result.push(p0);
+        }
+        if (d0 < -PLANE_EPSILON && d1 > PLANE_EPSILON)
+            || (d0 > PLANE_EPSILON && d1 < -PLANE_EPSILON)
+        {
+            result.push(p0 + (p1 - p0) * (d0 / (d0 - d1)));
+        }
+    }
+    result
+}
+
+/// An axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: DVec3,
+    pub max: DVec3,
+}
+
+impl Aabb {
+    pub fn size(self) -> DVec3 {
+        self.max - self.min
+    }
+    pub fn center(self) -> DVec3 {
+        (self.min + self.max) / 2.0
+    }
+    /// The smallest box containing both boxes.
+    pub fn union(self, other: Aabb) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+    /// Whether the boxes share any volume. Boxes that only touch do not overlap.
+    pub fn overlaps(self, other: Aabb) -> bool {
+        self.min.cmplt(other.max - PLANE_EPSILON).all()
+            && other.min.cmplt(self.max - PLANE_EPSILON).all()
+    }
+    /// Whether the boxes share any point, including boxes that only touch.
+    pub fn touches(self, other: Aabb) -> bool {
+        self.min.cmple(other.max + PLANE_EPSILON).all()
+            && other.min.cmple(self.max + PLANE_EPSILON).all()
+    }
+}
+
+impl Brush {
+    /// The smallest axis-aligned box containing the brush.
+    pub fn bounds(&self) -> Aabb {
+        Aabb {
+            min: self.vertices.iter().fold(DVec3::INFINITY, |a, b| a.min(*b)),
+            max: self
+                .vertices
+                .iter()
+                .fold(DVec3::NEG_INFINITY, |a, b| a.max(*b)),
+        }
+    }
+
+    /// The volume enclosed by the brush, from the signed volumes of the tetrahedra its outward
+    /// facing triangles form with the origin.
+    pub fn volume(&self) -> f64 {
+        self.triangles()
+            .map(|side| tetrahedron_volume(side.geom))
+            .sum()
+    }
 
-        // Just a check.
-        #[cfg(debug_assertions)]
-        assert_eq!(side_indices.len() % 3, 0);
+    /// The center of mass of the brush, assuming uniform density.
+    pub fn centroid(&self) -> DVec3 {
+        let volume = self.volume();
+        if volume <= 0.0 {
+            // A flat brush has no center of mass; fall back to the average vertex.
+            return self.vertices.iter().sum::<DVec3>() / self.vertices.len() as f64;
+        }
+        self.triangles()
+            .map(|side| {
+                let SideGeom([p0, p1, p2]) = side.geom;
+                tetrahedron_volume(side.geom) * (p0 + p1 + p2) / 4.0
+            })
+            .sum::<DVec3>()
+            / volume
+    }
+
+    /// The total area of the brush's sides.
+    pub fn area(&self) -> f64 {
+        self.triangles().map(|side| triangle_area(side.geom)).sum()
+    }
+
+    /// The area of each side, in the order of [to_sides_unique](Self::to_sides_unique).
+    pub fn face_areas(&self) -> Vec<f64> {
+        let unique = self.to_sides_unique();
+        let mut areas = vec![0.0; unique.len()];
+        for side in self.triangles() {
+            if let Some(face) = unique
+                .iter()
+                .position(|other| other.geom.equivalent(side.geom))
+            {
+                areas[face] += triangle_area(side.geom);
+            }
+        }
+        areas
+    }
 
-        use itertools::Itertools;
-        let sides = side_indices
-            .into_iter()
-            .tuples()
-            .map(|(i0, i1, i2)| (i1, i0, i2)) // Reorder to (p1-p0; p2-p0) order
-            .map(|(i0, i1, i2)| ([i0, i1, i2], SideMtrl::default()))
+    /// Whether a point is inside the brush or on its surface.
+    pub fn contains_point(&self, point: DVec3) -> bool {
+        self.planes()
+            .all(|plane| plane.distance_to(point) <= PLANE_EPSILON)
+    }
+
+    /// Where a ray first meets the brush, as a multiple of `direction` from `origin`, or `None` if
+    /// the ray misses. A ray starting inside the brush meets it at 0.
+    pub fn ray_intersection(&self, origin: DVec3, direction: DVec3) -> Option<f64> {
+        let mut enter: f64 = 0.0;
+        let mut exit = f64::INFINITY;
+        for plane in self.planes() {
+            let distance = plane.distance_to(origin);
+            let speed = plane.normal.dot(direction);
+            if speed == 0.0 {
+                if distance > PLANE_EPSILON {
+                    return None;
+                }
+                continue;
+            }
+            let t = -distance / speed;
+            if speed < 0.0 {
+                enter = enter.max(t);
+            } else {
+                exit = exit.min(t);
+            }
+            if enter > exit {
+                return None;
+            }
+        }
+        Some(enter)
+    }
+
+    /// Whether two brushes share any volume. Brushes that only touch do not overlap.
+    pub fn overlaps(&self, other: &Brush) -> bool {
+        if !self.bounds().overlaps(other.bounds()) {
+            return false;
+        }
+        // By the separating axis theorem, two convex brushes are apart if and only if they are
+        // apart along a side normal of either, or along the cross product of an edge of each.
+        let normals: Vec<DVec3> = self
+            .planes()
+            .chain(other.planes())
+            .map(|p| p.normal)
             .collect();
+        let edges = self.edge_directions();
+        let other_edges = other.edge_directions();
+        let crosses = edges.iter().flat_map(|a| {
+            other_edges
+                .iter()
+                .filter_map(move |b| a.cross(*b).try_normalize())
+        });
+        let separated = |axis: DVec3| {
+            let project = |brush: &Brush| {
+                brush
+                    .vertices
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                        (lo.min(v.dot(axis)), hi.max(v.dot(axis)))
+                    })
+            };
+            let (lo, hi) = project(self);
+            let (other_lo, other_hi) = project(other);
+            hi <= other_lo + PLANE_EPSILON || other_hi <= lo + PLANE_EPSILON
+        };
+        !normals.into_iter().chain(crosses).any(separated)
+    }
 
-        Self { vertices, sides }
+    fn planes(&self) -> impl Iterator<Item = Plane> + use<'_> {
+        self.triangles().filter_map(|side| side.geom.plane())
+    }
+
+    fn edge_directions(&self) -> Vec<DVec3> {
+        let mut directions: Vec<DVec3> = Vec::new();
+        for ([i0, i1, i2], _) in &self.sides {
+            for (a, b) in [(i0, i1), (i1, i2), (i2, i0)] {
+                let Some(direction) = (self.vertices[*b] - self.vertices[*a]).try_normalize()
+                else {
+                    continue;
+                };
+                if !directions
+                    .iter()
+                    .any(|other| other.cross(direction).length() < ALMOST_EQUAL_DELTA)
+                {
+                    directions.push(direction);
+                }
+            }
+        }
+        directions
     }
 }
 
+/// The signed volume of the tetrahedron formed by an outward facing triangle and the origin.
+fn tetrahedron_volume(SideGeom([p0, p1, p2]): SideGeom) -> f64 {
+    p0.dot(p2.cross(p1)) / 6.0
+}
+
+pub(crate) fn triangle_area(SideGeom([p0, p1, p2]): SideGeom) -> f64 {
+    (p0 - p1).cross(p2 - p1).length() / 2.0
+}
+
 impl Brush {
-    pub(crate) fn bake(&self) -> impl Display + use<'_> {
-        struct BrushDisp<'a>(&'a Brush);
+    pub(crate) fn bake(&self, writer: MapWriter) -> impl Display + use<'_> {
+        struct BrushDisp<'a>(&'a Brush, MapWriter);
         impl Display for BrushDisp<'_> {
             fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-                writeln!(f, "{{",)?;
+                let eol = self.1.line_ending;
+                let brush_def = self.1.format == MapFormat::BrushDef3;
+                write!(f, "{{{eol}")?;
+                if brush_def {
+                    write!(f, "brushDef3{eol}{{{eol}")?;
+                }
                 for side in self.0.to_sides_unique().iter() {
-                    writeln!(f, "{}", side.bake())?;
+                    write!(f, "{}{eol}", side.bake(self.1))?;
+                }
+                if brush_def {
+                    write!(f, "}}{eol}")?;
                 }
-                writeln!(f, "}}")?;
+                write!(f, "}}{eol}")?;
                 Ok(())
             }
         }
-        BrushDisp(self)
+        BrushDisp(self, writer)
     }
 }
 
@@ -351,11 +1177,142 @@ mod tests {
         };
 
         assert_eq!(
-            format!("{}", side.bake()),
+            format!("{}", side.bake(MapWriter::default())),
             "( 1.000000 2.000000 3.000000 ) ( 10.000000 20.000000 30.000000 ) ( 100.000000 200.000000 300.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0"
         );
     }
 
+    #[test]
+    fn bake_side_alignment() {
+        let side = Side {
+            geom: SideGeom([DVec3::ZERO, DVec3::Y, DVec3::Z]),
+            mtrl: SideMtrl {
+                offset_x: 16.0,
+                offset_y: -8.0,
+                rotation: 45.0,
+                scale_x: 0.25,
+                scale_y: 1.0,
+                content_flags: 1,
+                surface_flags: 2,
+                value: 3,
+                ..SideMtrl::new("mtrl/turf-green")
+            },
+        };
+
+        assert_eq!(
+            format!("{}", side.bake(MapWriter::default())),
+            "( 0.000000 0.000000 0.000000 ) ( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/turf-green 16 -8 45 0.25 1 1 2 3"
+        );
+    }
+
+    #[test]
+    fn bake_side_integer_coordinates() {
+        let writer = MapWriter {
+            integer_coordinates: true,
+            precision: 1,
+            ..Default::default()
+        };
+        let side = Side {
+            geom: SideGeom([
+                DVec3::new(0.2, 0.0, 0.0),
+                DVec3::new(0.0, 63.8, 0.0),
+                DVec3::new(0.0, 0.0, 64.4),
+            ]),
+            mtrl: SideMtrl::default(),
+        };
+        assert!(
+            format!("{}", side.bake(writer))
+                .starts_with("( 0 0 0 ) ( 0 64 0 ) ( 0 0 64 ) mtrl/invisible")
+        );
+
+        // Rounding this thin side would flip its plane, so it keeps its full precision.
+        let sliver = Side {
+            geom: SideGeom([
+                DVec3::new(0.0, 0.0, 0.0),
+                DVec3::new(10.0, 0.4, 0.0),
+                DVec3::new(20.0, 0.6, 0.0),
+            ]),
+            mtrl: SideMtrl::default(),
+        };
+        assert!(
+            format!("{}", sliver.bake(writer))
+                .starts_with("( 0.0 0.0 0.0 ) ( 10.0 0.4 0.0 ) ( 20.0 0.6 0.0 ) mtrl/invisible")
+        );
+    }
+
+    #[test]
+    fn face_texture_axes() {
+        // A ramp rising at 45 degrees along +x.
+        let ramp = SideGeom([DVec3::ZERO, DVec3::Y, DVec3::new(1.0, 1.0, 1.0)]);
+        let normal = ramp.normal().unwrap();
+        assert!(almost_equals(
+            &normal,
+            &DVec3::new(-1.0, 0.0, 1.0).normalize()
+        ));
+        for rotation in [0.0, 30.0, 90.0] {
+            let mtrl = SideMtrl {
+                rotation,
+                ..Default::default()
+            };
+            let [u, v] = mtrl.face_texture_axes(normal);
+            assert!(u.dot(normal).abs() < ALMOST_EQUAL_DELTA);
+            assert!(v.dot(normal).abs() < ALMOST_EQUAL_DELTA);
+            assert!(u.dot(v).abs() < ALMOST_EQUAL_DELTA);
+            assert!((u.length() - 1.0).abs() < ALMOST_EQUAL_DELTA);
+            assert!((v.length() - 1.0).abs() < ALMOST_EQUAL_DELTA);
+        }
+
+        // Axial faces keep the standard format's axes, rotation included.
+        let mtrl = SideMtrl {
+            rotation: 30.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            ..Default::default()
+        };
+        for normal in [DVec3::Z, DVec3::NEG_Z, DVec3::X, DVec3::NEG_X, DVec3::Y] {
+            let face = mtrl.face_texture_axes(normal);
+            let standard = mtrl.texture_axes(normal);
+            assert!(almost_equals(&face[0], &standard[0]));
+            assert!(almost_equals(&face[1], &standard[1]));
+        }
+
+        let side = Side {
+            geom: ramp,
+            mtrl: SideMtrl::default(),
+        };
+        let writer = MapWriter {
+            format: MapFormat::Valve220,
+            ..Default::default()
+        };
+        let text = side.bake(writer).to_string();
+        let axes: Vec<DVec3> = text
+            .split(['[', ']'])
+            .skip(1)
+            .step_by(2)
+            .map(|axis| {
+                let c: Vec<f64> = axis
+                    .split_whitespace()
+                    .map(|c| c.parse().unwrap())
+                    .collect();
+                DVec3::new(c[0], c[1], c[2])
+            })
+            .collect();
+        assert_eq!(axes.len(), 2);
+        assert!(
+            axes.iter()
+                .all(|axis| axis.dot(normal).abs() < ALMOST_EQUAL_DELTA)
+        );
+        let writer = MapWriter {
+            format: MapFormat::BrushDef3,
+            precision: 3,
+            ..Default::default()
+        };
+        // Doom3 measures this face along -y and down the slope, so u and v swap places.
+        let text = side.bake(writer).to_string();
+        assert!(text.starts_with("( -0.707 0.000 0.707 0.000 ) ( ( 0 -0.01562"));
+        assert!(text.ends_with(" 0 ) ( 0.015625 0 0 ) ) \"mtrl/invisible\" 0 0 0"));
+    }
+
     #[test]
     fn bake_brush_pyramid() {
         let vertices = vec![
@@ -369,14 +1326,345 @@ mod tests {
         let brush = Brush::try_from_vertices(&vertices, Some(1000)).unwrap();
 
         let should_eq_str = r"{
-( 0.000000 0.000000 0.000000 ) ( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
-( 0.000000 1.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
 ( 1.000000 0.000000 0.000000 ) ( 0.000000 0.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
-( 0.000000 0.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) ( 0.000000 1.000000 0.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
+( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
+( 0.000000 1.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
+( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) ( 0.000000 0.000000 0.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
 }
 ";
-        println!("{}", brush.bake());
+        println!("{}", brush.bake(MapWriter::default()));
         println!("{}", should_eq_str);
-        assert_eq!(format!("{}", brush.bake()), should_eq_str);
+        assert_eq!(
+            format!("{}", brush.bake(MapWriter::default())),
+            should_eq_str
+        );
+    }
+
+    fn unit_cube_planes() -> Vec<Plane> {
+        vec![
+            Plane::new(DVec3::X, 1.0).unwrap(),
+            Plane::new(DVec3::NEG_X, 0.0).unwrap(),
+            Plane::new(DVec3::Y, 1.0).unwrap(),
+            Plane::new(DVec3::NEG_Y, 0.0).unwrap(),
+            Plane::new(DVec3::Z, 1.0).unwrap(),
+            Plane::new(DVec3::NEG_Z, 0.0).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn brush_from_planes() {
+        let mut planes = unit_cube_planes();
+        // Redundant planes, one touching a corner and one entirely outside the cube.
+        planes.push(Plane::new(DVec3::ONE, 3.0).unwrap());
+        planes.push(Plane::new(DVec3::new(1.0, 1.0, 0.0), 10.0).unwrap());
+        // A duplicate.
+        planes.push(Plane::new(DVec3::X * 2.0, 2.0).unwrap());
+
+        let brush = Brush::try_from_planes(&planes).unwrap();
+        assert_eq!(brush.vertices().len(), 8);
+        assert_eq!(brush.to_sides_unique().len(), 6);
+        for x in [0.0, 1.0] {
+            for y in [0.0, 1.0] {
+                for z in [0.0, 1.0] {
+                    let corner = DVec3::new(x, y, z);
+                    assert!(brush.vertices().iter().any(|v| almost_equals(v, &corner)));
+                }
+            }
+        }
+        for side in brush.triangles() {
+            let normal = side.geom.normal().unwrap();
+            assert!(unit_cube_planes().iter().any(|plane| {
+                plane.equivalent(side.geom.plane().unwrap()) && normal.dot(plane.normal) > 0.0
+            }));
+        }
+    }
+
+    #[test]
+    fn split_brush() {
+        let mut brush = Brush::try_from_planes(&unit_cube_planes()).unwrap();
+        for mtrl in brush.materials_mut() {
+            mtrl.texture = "mtrl/turf-grey".to_string();
+        }
+
+        let plane = Plane::new(DVec3::new(1.0, 0.0, 1.0), 1.0).unwrap();
+        let (back, front) = brush.split(plane).unwrap();
+        let (back, front) = (back.unwrap(), front.unwrap());
+        assert!((back.volume() - 0.5).abs() < 1e-9);
+        assert!((front.volume() - 0.5).abs() < 1e-9);
+        assert_eq!(back.to_sides_unique().len(), 5);
+        assert_eq!(front.to_sides_unique().len(), 5);
+        for vertex in back.vertices() {
+            assert!(plane.distance_to(*vertex) < 1e-9);
+        }
+        let cut = |brush: &Brush, plane: Plane| {
+            brush
+                .triangles()
+                .filter(|side| side.geom.plane().unwrap().equivalent(plane))
+                .all(|side| side.mtrl == SideMtrl::default())
+        };
+        assert!(cut(&back, plane));
+        assert!(cut(&front, plane.flipped()));
+        assert!(
+            back.triangles()
+                .filter(|side| !side.geom.plane().unwrap().equivalent(plane))
+                .all(|side| side.mtrl.texture == "mtrl/turf-grey")
+        );
+
+        // Planes that touch the brush without cutting it leave it whole.
+        let (back, front) = brush.split(Plane::new(DVec3::X, 1.0).unwrap()).unwrap();
+        assert_eq!(back.unwrap().vertices(), brush.vertices());
+        assert!(front.is_none());
+        let (back, front) = split_brushes(&[brush], Plane::new(DVec3::Z, -1.0).unwrap()).unwrap();
+        assert!(back.is_empty());
+        assert_eq!(front.len(), 1);
+    }
+
+    #[test]
+    fn split_near_face() {
+        let cube = Brush::try_from_planes(&unit_cube_planes()).unwrap();
+        // Tilted planes leaving one edge of the cube just in front and the other on or just behind.
+        for front in [1.5 * PLANE_EPSILON, 3.0 * PLANE_EPSILON, 1e-4] {
+            for behind in [0.0, -0.5 * PLANE_EPSILON, -2.0 * PLANE_EPSILON] {
+                let normal = DVec3::new(1.0, front - behind, 0.0);
+                let plane = Plane::new(normal, 1.0 - behind).unwrap();
+                let (back, front) = cube.split(plane).unwrap();
+                let (back, front) = (back.unwrap(), front.unwrap());
+                assert!((back.volume() + front.volume() - 1.0).abs() < 1e-9);
+                assert!(front.volume() < 1e-4);
+                assert!(front.vertices().iter().all(|v| v.x > 1.0 - 1e-4));
+            }
+        }
+    }
+
+    #[test]
+    fn subtract_brush() {
+        let block = Brush::try_from_planes(&[
+            Plane::new(DVec3::X, 3.0).unwrap(),
+            Plane::new(DVec3::NEG_X, 0.0).unwrap(),
+            Plane::new(DVec3::Y, 3.0).unwrap(),
+            Plane::new(DVec3::NEG_Y, 0.0).unwrap(),
+            Plane::new(DVec3::Z, 1.0).unwrap(),
+            Plane::new(DVec3::NEG_Z, 0.0).unwrap(),
+        ])
+        .unwrap();
+        let mut hole = Brush::try_from_planes(&[
+            Plane::new(DVec3::X, 2.0).unwrap(),
+            Plane::new(DVec3::NEG_X, -1.0).unwrap(),
+            Plane::new(DVec3::Y, 2.0).unwrap(),
+            Plane::new(DVec3::NEG_Y, -1.0).unwrap(),
+            Plane::new(DVec3::Z, 2.0).unwrap(),
+            Plane::new(DVec3::NEG_Z, 1.0).unwrap(),
+        ])
+        .unwrap();
+        for mtrl in hole.materials_mut() {
+            mtrl.texture = "mtrl/turf-grey".to_string();
+        }
+
+        let pieces = block.subtract(&hole).unwrap();
+        assert_eq!(pieces.len(), 4);
+        let volume: f64 = pieces.iter().map(Brush::volume).sum();
+        assert!((volume - 8.0).abs() < 1e-9);
+        // The walls of the hole take the hole's material.
+        let walls: Vec<Side> = pieces
+            .iter()
+            .flat_map(Brush::to_sides_unique)
+            .filter(|side| side.mtrl.texture == "mtrl/turf-grey")
+            .collect();
+        assert_eq!(walls.len(), 4);
+        for wall in walls {
+            let plane = wall.geom.plane().unwrap();
+            assert!(
+                hole.to_sides_unique()
+                    .iter()
+                    .any(|side| { side.geom.plane().unwrap().flipped().equivalent(plane) })
+            );
+        }
+
+        let away = Brush::try_from_planes(&unit_cube_planes())
+            .unwrap()
+            .split(Plane::new(DVec3::Z, 0.5).unwrap())
+            .unwrap()
+            .0
+            .unwrap();
+        let pieces = subtract_brushes(std::slice::from_ref(&away), &[hole]).unwrap();
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].vertices(), away.vertices());
+
+        // A cutter overlapping the block by a sliver still leaves the rest of the block.
+        let sliver = Brush::try_from_planes(&[
+            Plane::new(DVec3::X, 4.0).unwrap(),
+            Plane::new(DVec3::NEG_X, -(3.0 - 1.5 * PLANE_EPSILON)).unwrap(),
+            Plane::new(DVec3::Y, 2.0).unwrap(),
+            Plane::new(DVec3::NEG_Y, -1.0).unwrap(),
+            Plane::new(DVec3::Z, 2.0).unwrap(),
+            Plane::new(DVec3::NEG_Z, 1.0).unwrap(),
+        ])
+        .unwrap();
+        let pieces = block.subtract(&sliver).unwrap();
+        let volume: f64 = pieces.iter().map(Brush::volume).sum();
+        assert!((volume - 9.0).abs() < 1e-5);
+
+        // A flat brush cannot be cut into pieces, which is reported rather than ignored.
+        let flat = Brush {
+            vertices: vec![
+                DVec3::new(-1.0, -1.0, 1.5),
+                DVec3::new(4.0, -1.0, 1.5),
+                DVec3::new(4.0, 4.0, 1.5),
+                DVec3::new(-1.0, 4.0, 1.5),
+            ],
+            sides: vec![
+                ([1, 0, 2], SideMtrl::default()),
+                ([2, 0, 3], SideMtrl::default()),
+            ],
+        };
+        assert!(matches!(
+            flat.subtract(&sliver),
+            Err(BrushError::ConvexHullFail(_))
+        ));
+    }
+
+    #[test]
+    fn subtract_curve() {
+        use crate::curve::Curve;
+        use crate::curve::curve_classic::CurveClassic;
+        use crate::curve::extrude::path::Line;
+        use crate::curve::extrude::profile::Circle;
+        use crate::curve::extrude::{ProfileOrientation, ProfilePlane, extrude};
+
+        let floor = CurveClassic {
+            n: 8,
+            ri0: 32.0,
+            ro0: 64.0,
+            ri1: 32.0,
+            ro1: 64.0,
+            theta0: 0.0,
+            theta1: 90.0,
+            t: 8.0,
+        }
+        .bake()
+        .unwrap();
+        let circle = Circle::new(16, 48.0).unwrap();
+        let cylinder = extrude(
+            1,
+            &circle,
+            &Line::new(0.0, 0.0, 16.0),
+            ProfileOrientation::Constant(ProfilePlane::XY),
+        )
+        .unwrap();
+
+        let carved = subtract_brushes(&floor, &cylinder).unwrap();
+        let volume = |brushes: &[Brush]| brushes.iter().map(Brush::volume).sum::<f64>();
+        assert!(volume(&carved) < volume(&floor));
+        let inradius = 48.0 * (std::f64::consts::PI / 16.0).cos();
+        for brush in &carved {
+            for vertex in brush.vertices() {
+                assert!(vertex.truncate().length() > inradius - 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn brush_queries() {
+        // A 1x1x1 cube with one corner cut off, leaving a triangular side.
+        let mut planes_cut = unit_cube_planes();
+        planes_cut.push(Plane::new(DVec3::ONE, 2.5).unwrap());
+        let brush = Brush::try_from_planes(&planes_cut).unwrap();
+        let cube = Brush::try_from_planes(&unit_cube_planes()).unwrap();
+
+        let bounds = brush.bounds();
+        assert_eq!(bounds.min, DVec3::ZERO);
+        assert_eq!(bounds.max, DVec3::ONE);
+        assert_eq!(bounds.center(), DVec3::splat(0.5));
+
+        // The corner removed is a tetrahedron with legs of 0.5.
+        let corner = 0.5f64.powi(3) / 6.0;
+        assert!((brush.volume() - (1.0 - corner)).abs() < 1e-9);
+        let centroid =
+            (DVec3::splat(0.5) - corner * DVec3::splat(1.0 - 0.5 / 4.0)) / (1.0 - corner);
+        assert!(brush.centroid().distance(centroid) < 1e-9);
+
+        let areas = brush.face_areas();
+        assert_eq!(areas.len(), 7);
+        let triangle = 0.5f64.powi(2) * 3f64.sqrt() / 4.0 * 2.0;
+        assert!((areas.iter().sum::<f64>() - brush.area()).abs() < 1e-9);
+        assert!((brush.area() - (6.0 - 3.0 * 0.125 + triangle)).abs() < 1e-9);
+        assert!((cube.area() - 6.0).abs() < 1e-9);
+
+        assert!(brush.contains_point(DVec3::splat(0.5)));
+        assert!(brush.contains_point(DVec3::ZERO));
+        assert!(!brush.contains_point(DVec3::splat(0.95)));
+
+        let hit = |origin, direction| brush.ray_intersection(origin, direction);
+        assert_eq!(hit(DVec3::new(-1.0, 0.5, 0.5), DVec3::X), Some(1.0));
+        assert_eq!(hit(DVec3::new(-1.0, 0.5, 0.5), DVec3::X * 4.0), Some(0.25));
+        assert_eq!(hit(DVec3::splat(0.5), DVec3::Z), Some(0.0));
+        assert_eq!(hit(DVec3::new(-1.0, 0.5, 0.5), DVec3::NEG_X), None);
+        assert_eq!(hit(DVec3::new(-1.0, 2.0, 0.5), DVec3::X), None);
+        let t = hit(DVec3::splat(2.0), DVec3::NEG_ONE).unwrap();
+        assert!((t - (2.0 - 2.5 / 3.0)).abs() < 1e-9);
+
+        assert!(brush.overlaps(&cube));
+        let beside = cube
+            .split(Plane::new(DVec3::X, 0.5).unwrap())
+            .unwrap()
+            .1
+            .unwrap();
+        assert!(brush.overlaps(&beside));
+        assert!(
+            !cube.overlaps(&Brush {
+                vertices: cube
+                    .vertices()
+                    .iter()
+                    .map(|v| *v + DVec3::X * 2.0)
+                    .collect(),
+                sides: cube.sides.clone(),
+            })
+        );
+        let next = Aabb {
+            min: DVec3::X,
+            max: DVec3::new(2.0, 1.0, 1.0),
+        };
+        assert!(cube.bounds().touches(next) && !cube.bounds().overlaps(next));
+        let apart = Aabb {
+            min: DVec3::splat(2.0),
+            max: DVec3::splat(3.0),
+        };
+        assert!(!cube.bounds().touches(apart));
+        // Close along every axis, but apart across the cut corner.
+        let far_corner = Brush::try_from_planes(&[
+            Plane::new(DVec3::NEG_ONE, -2.6).unwrap(),
+            Plane::new(DVec3::X, 1.0).unwrap(),
+            Plane::new(DVec3::Y, 1.0).unwrap(),
+            Plane::new(DVec3::Z, 1.0).unwrap(),
+        ])
+        .unwrap();
+        assert!(brush.bounds().overlaps(far_corner.bounds()));
+        assert!(!brush.overlaps(&far_corner));
+    }
+
+    #[test]
+    fn brush_from_planes_errors() {
+        let mut planes = unit_cube_planes();
+        planes.pop();
+        assert_eq!(
+            Brush::try_from_planes(&planes).unwrap_err(),
+            BrushError::Unbounded
+        );
+
+        let mut planes = unit_cube_planes();
+        planes.push(Plane::new(DVec3::X, -1.0).unwrap());
+        assert_eq!(
+            Brush::try_from_planes(&planes).unwrap_err(),
+            BrushError::Empty
+        );
+
+        let sides = [Side {
+            geom: SideGeom([DVec3::ZERO, DVec3::X, DVec3::X * 2.0]),
+            mtrl: SideMtrl::default(),
+        }];
+        assert_eq!(
+            Brush::try_from_sides(&sides).unwrap_err(),
+            BrushError::DegenerateSide { index: 0 }
+        );
     }
 }
@@ -0,0 +1,696 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Building brushes from the convex hull of a set of points.
+
+use crate::map::geometry::{Brush, SideMtrl};
+use glam::DVec3;
+use std::collections::{BTreeSet, HashMap};
+use thiserror::Error;
+
+/// Points closer than this to a plane, relative to the size of the input, are treated as lying on
+/// it.
+const HULL_EPSILON: f64 = 1e-10;
+
+/// The iteration limit used when building brushes from the hull of their vertices.
+pub(crate) const MAX_HULL_ITER: Option<usize> = Some(10_000);
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum HullError {
+    #[error("A brush needs at least 4 vertices, but {count} were given.")]
+    NotEnoughVertices { count: usize },
+    #[error("Vertex {index} is not finite: {vertex}.")]
+    NonFinite { index: usize, vertex: DVec3 },
+    #[error("Every vertex is in the same place as vertex {index}.")]
+    Coincident { index: usize },
+    #[error("Every vertex lies on the line through vertices {a} and {b}.")]
+    Collinear { a: usize, b: usize },
+    #[error("Every vertex lies on the plane through vertices {a}, {b} and {c}.")]
+    Coplanar { a: usize, b: usize, c: usize },
+    #[error("The hull was not finished after {max_iter} iterations.")]
+    TooManyIterations { max_iter: usize },
+    #[error("The edge from vertex {a} to vertex {b} has no face on its other side.")]
+    OpenEdge { a: usize, b: usize },
+    #[error("The side through vertices {a}, {b} and {c} has no edges with other sides.")]
+    NoBoundary { a: usize, b: usize, c: usize },
+}
+
+/// A triangle of the hull under construction, wound counter-clockwise seen from outside.
+struct Face {
+    vertices: [usize; 3],
+    normal: DVec3,
+    dist: f64,
+    /// Points in front of this face that are not yet on the hull.
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl Face {
+    fn new(points: &[DVec3], vertices: [usize; 3]) -> Self {
+        let [a, b, c] = vertices.map(|i| points[i]);
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        Self {
+            vertices,
+            normal,
+            dist: normal.dot(a),
+            outside: Vec::new(),
+            alive: true,
+        }
+    }
+
+    fn distance_to(&self, point: DVec3) -> f64 {
+        self.normal.dot(point) - self.dist
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+struct Hull<'a> {
+    points: &'a [DVec3],
+    epsilon: f64,
+    faces: Vec<Face>,
+    /// The face on the left of each directed edge.
+    edges: HashMap<(usize, usize), usize>,
+}
+
+impl Hull<'_> {
+    fn add_face(&mut self, vertices: [usize; 3]) -> usize {
+        let face = Face::new(self.points, vertices);
+        let index = self.faces.len();
+        for edge in face.edges() {
+            self.edges.insert(edge, index);
+        }
+        self.faces.push(face);
+        index
+    }
+
+    fn remove_face(&mut self, index: usize) {
+        self.faces[index].alive = false;
+        for edge in self.faces[index].edges() {
+            self.edges.remove(&edge);
+        }
+    }
+
+    /// The face on the other side of a face's edge. Rounding on nearly degenerate input can leave
+    /// an edge without one.
+    fn neighbour(&self, (a, b): (usize, usize)) -> Result<usize, HullError> {
+        self.edges
+            .get(&(b, a))
+            .copied()
+            .ok_or(HullError::OpenEdge { a, b })
+    }
+
+    /// Give each point to the first face it is in front of. Points behind every face are inside
+    /// the hull and are dropped.
+    fn assign(&mut self, points: impl IntoIterator<Item = usize>, faces: &[usize]) {
+        for point in points {
+            if let Some(&face) = faces
+                .iter()
+                .find(|&&face| self.faces[face].distance_to(self.points[point]) > self.epsilon)
+            {
+                self.faces[face].outside.push(point);
+            }
+        }
+    }
+
+    /// Add the furthest point in front of `face` to the hull, replacing every face that can see it.
+    /// Returns the new faces.
+    fn expand(&mut self, face: usize) -> Result<Vec<usize>, HullError> {
+        let outside = std::mem::take(&mut self.faces[face].outside);
+        let (eye, _) = outside
+            .iter()
+            .enumerate()
+            .fold((0, f64::MIN), |best, (i, point)| {
+                let distance = self.faces[face].distance_to(self.points[*point]);
+                if distance > best.1 {
+                    (i, distance)
+                } else {
+                    best
+                }
+            });
+        let point = self.points[outside[eye]];
+        let eye_index = outside[eye];
+        self.faces[face].outside = outside;
+
+        // The faces that can see the point form a connected patch; its boundary is the horizon.
+        let mut visible = vec![face];
+        let mut horizon = Vec::new();
+        let mut stack = vec![face];
+        while let Some(current) = stack.pop() {
+            for edge in self.faces[current].edges() {
+                let other = self.neighbour(edge)?;
+                if visible.contains(&other) {
+                    continue;
+                }
+                if self.faces[other].distance_to(point) > self.epsilon {
+                    visible.push(other);
+                    stack.push(other);
+                } else {
+                    horizon.push(edge);
+                }
+            }
+        }
+
+        let mut orphans = Vec::new();
+        for &index in &visible {
+            orphans.append(&mut self.faces[index].outside);
+            self.remove_face(index);
+        }
+        let new_faces: Vec<usize> = horizon
+            .into_iter()
+            .map(|(a, b)| self.add_face([a, b, eye_index]))
+            .collect();
+        self.assign(orphans.into_iter().filter(|&i| i != eye_index), &new_faces);
+        Ok(new_faces)
+    }
+
+    /// Group neighbouring faces that lie on the same plane, returning a group number for each
+    /// face.
+    fn coplanar_groups(&self) -> Result<Vec<usize>, HullError> {
+        let mut group: Vec<usize> = (0..self.faces.len()).collect();
+        fn root(group: &mut [usize], mut i: usize) -> usize {
+            while group[i] != i {
+                group[i] = group[group[i]];
+                i = group[i];
+            }
+            i
+        }
+        for (index, face) in self.faces.iter().enumerate() {
+            if !face.alive {
+                continue;
+            }
+            for edge in face.edges() {
+                let neighbour = self.neighbour(edge)?;
+                let other = &self.faces[neighbour];
+                let on_plane = |a: &Face, b: &Face| {
+                    b.vertices
+                        .iter()
+                        .all(|&v| a.distance_to(self.points[v]).abs() <= self.epsilon)
+                };
+                if on_plane(face, other) && on_plane(other, face) {
+                    let (a, b) = (root(&mut group, index), root(&mut group, neighbour));
+                    group[a] = b;
+                }
+            }
+        }
+        Ok((0..group.len()).map(|i| root(&mut group, i)).collect())
+    }
+}
+
+/// Find the convex hull of a set of points, merging coplanar triangles into single sides.
+pub(crate) fn convex_hull(points: &[DVec3], max_iter: Option<usize>) -> Result<Brush, HullError> {
+    if points.len() < 4 {
+        return Err(HullError::NotEnoughVertices {
+            count: points.len(),
+        });
+    }
+    if let Some((index, vertex)) = points
+        .iter()
+        .enumerate()
+        .find(|(_, vertex)| !vertex.is_finite())
+    {
+        return Err(HullError::NonFinite {
+            index,
+            vertex: *vertex,
+        });
+    }
+
+    let epsilon = epsilon(points);
+
+    let [a, b, c, d] = initial_simplex(points, epsilon)?;
+    let mut hull = Hull {
+        points,
+        epsilon,
+        faces: Vec::new(),
+        edges: HashMap::new(),
+    };
+    // Wind the tetrahedron so that every face looks away from d.
+    let [b, c] = if Face::new(points, [a, b, c]).distance_to(points[d]) > 0.0 {
+        [c, b]
+    } else {
+        [b, c]
+    };
+    let initial = [
+        hull.add_face([a, b, c]),
+        hull.add_face([a, d, b]),
+        hull.add_face([b, d, c]),
+        hull.add_face([c, d, a]),
+    ];
+    hull.assign(
+        (0..points.len()).filter(|i| ![a, b, c, d].contains(i)),
+        &initial,
+    );
+
+    // Faces that may still have points in front of them. Expanding the lowest first keeps the
+    // output the same from run to run. Faces removed since they were added are skipped.
+    let mut pending: BTreeSet<usize> = initial.into_iter().collect();
+    let mut iterations = 0;
+    while let Some(face) = pending.pop_first() {
+        if !hull.faces[face].alive || hull.faces[face].outside.is_empty() {
+            continue;
+        }
+        if let Some(max_iter) = max_iter
+            && iterations >= max_iter
+        {
+            return Err(HullError::TooManyIterations { max_iter });
+        }
+        pending.extend(hull.expand(face)?);
+        iterations += 1;
+    }
+
+    to_brush(&hull)
+}
+
+/// Find four points that span a tetrahedron, or explain why there are none.
+fn initial_simplex(points: &[DVec3], epsilon: f64) -> Result<[usize; 4], HullError> {
+    let furthest = |distance: &dyn Fn(DVec3) -> f64| {
+        (0..points.len())
+            .map(|i| (i, distance(points[i])))
+            .fold(
+                (0, f64::MIN),
+                |best, next| if next.1 > best.1 { next } else { best },
+            )
+    };
+
+    // The two points furthest apart along an axis.
+    let mut a = 0;
+    let mut b = 0;
+    let mut extent = 0.0;
+    for axis in [DVec3::X, DVec3::Y, DVec3::Z] {
+        let (min, _) = furthest(&|point| -point.dot(axis));
+        let (max, _) = furthest(&|point| point.dot(axis));
+        let length = (points[max] - points[min]).dot(axis);
+        if length > extent {
+            (a, b, extent) = (min, max, length);
+        }
+    }
+    if extent <= epsilon {
+        return Err(HullError::Coincident { index: a });
+    }
+
+    let direction = (points[b] - points[a]).normalize();
+    let (c, distance) = furthest(&|point| {
+        (point - points[a])
+            .reject_from_normalized(direction)
+            .length()
+    });
+    if distance <= epsilon {
+        return Err(HullError::Collinear { a, b });
+    }
+
+    let normal = (points[b] - points[a])
+        .cross(points[c] - points[a])
+        .normalize();
+    let (d, distance) = furthest(&|point| normal.dot(point - points[a]).abs());
+    if distance <= epsilon {
+        return Err(HullError::Coplanar { a, b, c });
+    }
+
+    Ok([a, b, c, d])
+}
+
+/// Turn the finished hull into a brush, with one fan of triangles for each group of coplanar
+/// faces.
+fn to_brush(hull: &Hull) -> Result<Brush, HullError> {
+    let groups = hull.coplanar_groups()?;
+    let mut polygons: Vec<Vec<usize>> = Vec::new();
+    let mut done = vec![false; hull.faces.len()];
+    for (index, face) in hull.faces.iter().enumerate() {
+        if !face.alive || done[groups[index]] {
+            continue;
+        }
+        done[groups[index]] = true;
+        let members: Vec<usize> = (0..hull.faces.len())
+            .filter(|&i| hull.faces[i].alive && groups[i] == groups[index])
+            .collect();
+        if members.len() == 1 {
+            polygons.push(face.vertices.to_vec());
+            continue;
+        }
+
+        // The boundary of the group is made of the edges shared with other groups.
+        let mut next = HashMap::new();
+        for edge in members.iter().flat_map(|&i| hull.faces[i].edges()) {
+            if groups[hull.neighbour(edge)?] != groups[index] {
+                next.insert(edge.0, edge.1);
+            }
+        }
+        let Some(&start) = next.keys().min() else {
+            let [a, b, c] = face.vertices;
+            return Err(HullError::NoBoundary { a, b, c });
+        };
+        let mut polygon = vec![start];
+        while let Some(&vertex) = next.get(polygon.last().unwrap()) {
+            if vertex == start || polygon.len() > next.len() {
+                break;
+            }
+            polygon.push(vertex);
+        }
+        if polygon.len() == next.len() {
+            polygons.push(polygon);
+        } else {
+            // The boundary is not a single loop, so keep the faces as they are.
+            polygons.extend(members.iter().map(|&i| hull.faces[i].vertices.to_vec()));
+        }
+    }
+
+    // Keep the vertices in the order they were given.
+    let mut used: Vec<usize> = polygons.iter().flatten().copied().collect();
+    used.sort_unstable();
+    used.dedup();
+    let remap: HashMap<usize, usize> = used
+        .iter()
+        .enumerate()
+        .map(|(new, &old)| (old, new))
+        .collect();
+
+    let mut sides = Vec::new();
+    for polygon in &polygons {
+        for k in 1..polygon.len() - 1 {
+            let [a, b, c] = [polygon[0], polygon[k], polygon[k + 1]];
+            let area = (hull.points[b] - hull.points[a])
+                .cross(hull.points[c] - hull.points[a])
+                .length();
+            if area <= hull.epsilon * hull.epsilon {
+                continue;
+            }
+            // Brush sides put the middle point first.
+            sides.push(([remap[&b], remap[&a], remap[&c]], SideMtrl::default()));
+        }
+    }
+
+    Ok(Brush {
+        vertices: used.iter().map(|&i| hull.points[i]).collect(),
+        sides,
+    })
+}
+
+/// The distance within which points count as lying on a plane, scaled to the size of the input.
+fn epsilon(points: &[DVec3]) -> f64 {
+    let scale = points
+        .iter()
+        .map(|point| point.abs().max_element())
+        .fold(1.0, f64::max);
+    HULL_EPSILON * scale
+}
+
+/// Build a prism or frustum directly from its two end rings, without searching for a hull.
+///
+/// The rings must have the same number of points, in the same order around the profile. Returns
+/// `None` unless both ends and every side are flat, convex polygons meeting at convex edges, so
+/// the result is exactly the convex hull of the rings.
+///
+/// Sides come in a fixed order: the side spanning points `i` and `i + 1` of the rings for each
+/// `i`, followed by the end caps at `ring0` and `ring1`.
+pub(crate) fn prism(ring0: &[DVec3], ring1: &[DVec3]) -> Option<Brush> {
+    let n = ring0.len();
+    if n < 3 || ring1.len() != n {
+        return None;
+    }
+    let vertices: Vec<DVec3> = ring0.iter().chain(ring1).copied().collect();
+    if !vertices.iter().all(|vertex| vertex.is_finite()) {
+        return None;
+    }
+    let epsilon = epsilon(&vertices);
+    let center = vertices.iter().sum::<DVec3>() / vertices.len() as f64;
+
+    let mut faces: Vec<Vec<usize>> = (0..n)
+        .map(|i| {
+            let j = (i + 1) % n;
+            vec![i, j, n + j, n + i]
+        })
+        .collect();
+    faces.push((0..n).collect());
+    faces.push((n..2 * n).collect());
+
+    // Orient every face to look away from the center, rejecting any that are not flat and convex.
+    let mut planes = Vec::with_capacity(faces.len());
+    for face in faces.iter_mut() {
+        let points: Vec<DVec3> = face.iter().map(|&i| vertices[i]).collect();
+        let mut normal = convex_polygon_normal(&points, epsilon)?;
+        let dist = normal.dot(points[0]);
+        if normal.dot(center) > dist {
+            face.reverse();
+            normal = -normal;
+        }
+        let dist = normal.dot(points[0]);
+        if dist - normal.dot(center) <= epsilon {
+            return None;
+        }
+        planes.push((normal, dist));
+    }
+
+    // Every edge must be convex, so each side's neighbours lie behind it. A neighbour is a convex
+    // polygon sharing an edge with the side, so its centroid lies behind exactly when all its
+    // points do.
+    let centroid =
+        |face: &[usize]| face.iter().map(|&i| vertices[i]).sum::<DVec3>() / face.len() as f64;
+    let (start, end) = (n, n + 1);
+    for i in 0..n {
+        let (normal, dist) = planes[i];
+        let behind = |face: usize| normal.dot(centroid(&faces[face])) - dist < -epsilon;
+        if ![(i + 1) % n, (i + n - 1) % n, start, end]
+            .into_iter()
+            .all(behind)
+        {
+            return None;
+        }
+        for cap in [start, end] {
+            let (cap_normal, cap_dist) = planes[cap];
+            if cap_normal.dot(centroid(&faces[i])) - cap_dist >= -epsilon {
+                return None;
+            }
+        }
+    }
+
+    let sides = faces
+        .iter()
+        .flat_map(|face| {
+            (1..face.len() - 1).filter_map(|k| {
+                let [a, b, c] = [face[0], face[k], face[k + 1]];
+                let area = (vertices[b] - vertices[a])
+                    .cross(vertices[c] - vertices[a])
+                    .length();
+                // Brush sides put the middle point first.
+                (area > epsilon * epsilon).then_some(([b, a, c], SideMtrl::default()))
+            })
+        })
+        .collect();
+    Some(Brush { vertices, sides })
+}
+
+/// The unit normal of a flat, convex polygon whose points wind counter-clockwise around it, or
+/// `None` if the polygon is bent, not convex, winds around more than once, or has repeated points.
+fn convex_polygon_normal(points: &[DVec3], epsilon: f64) -> Option<DVec3> {
+    let center = points.iter().sum::<DVec3>() / points.len() as f64;
+    let normal = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| (*a - center).cross(*b - center))
+        .sum::<DVec3>()
+        .try_normalize()?;
+    if points
+        .iter()
+        .any(|point| normal.dot(*point - center).abs() > epsilon)
+    {
+        return None;
+    }
+
+    let mut turning = 0.0;
+    for i in 0..points.len() {
+        let before = points[i] - points[(i + points.len() - 1) % points.len()];
+        let after = points[(i + 1) % points.len()] - points[i];
+        if before.length() <= epsilon || after.length() <= epsilon {
+            return None;
+        }
+        let turn = normal.dot(before.cross(after)).atan2(before.dot(after));
+        if turn < -HULL_EPSILON {
+            return None;
+        }
+        turning += turn;
+    }
+    ((turning - std::f64::consts::TAU).abs() < 1e-6).then_some(normal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::geometry::Side;
+
+    fn cube() -> Vec<DVec3> {
+        (0..8)
+            .map(|i| DVec3::new((i & 1) as f64, (i >> 1 & 1) as f64, (i >> 2 & 1) as f64))
+            .collect()
+    }
+
+    fn outward(brush: &Brush) -> bool {
+        let centroid = brush.centroid();
+        brush
+            .triangles()
+            .all(|Side { geom, .. }| geom.plane().unwrap().distance_to(centroid) < 0.0)
+    }
+
+    #[test]
+    fn hull_merges_coplanar_faces() {
+        let mut points = cube();
+        // Points on the faces and edges, and a few barely outside them.
+        points.push(DVec3::new(0.5, 0.5, 1.0));
+        points.push(DVec3::new(0.5, 0.0, 0.0));
+        points.push(DVec3::new(0.25, 0.75, 1.0 + 1e-12));
+        points.push(DVec3::new(1.0 - 1e-13, 0.5, 0.5));
+        points.push(DVec3::splat(0.5));
+
+        let brush = convex_hull(&points, None).unwrap();
+        assert_eq!(brush.vertices(), &cube());
+        assert_eq!(brush.to_sides_unique().len(), 6);
+        assert_eq!(brush.triangles().count(), 12);
+        assert!((brush.volume() - 1.0).abs() < 1e-9);
+        assert!(outward(&brush));
+    }
+
+    #[test]
+    fn hull_of_many_points() {
+        // Points on a sphere, so every point is a vertex of the hull.
+        let n = 24;
+        let mut points = vec![DVec3::Z * 64.0, DVec3::NEG_Z * 64.0];
+        for i in 1..n {
+            for j in 0..n {
+                let (theta, phi) = (
+                    std::f64::consts::PI * i as f64 / n as f64,
+                    std::f64::consts::TAU * j as f64 / n as f64,
+                );
+                points.push(
+                    64.0 * DVec3::new(
+                        theta.sin() * phi.cos(),
+                        theta.sin() * phi.sin(),
+                        theta.cos(),
+                    ),
+                );
+            }
+        }
+        let brush = convex_hull(&points, None).unwrap();
+        assert_eq!(brush.vertices().len(), points.len());
+        // A closed surface of triangles has two triangles for every vertex, less four.
+        assert_eq!(brush.triangles().count(), 2 * points.len() - 4);
+        assert!(outward(&brush));
+        assert!(brush.volume() < 4.0 / 3.0 * std::f64::consts::PI * 64f64.powi(3));
+
+        assert_eq!(
+            convex_hull(&points, Some(10)).unwrap_err(),
+            HullError::TooManyIterations { max_iter: 10 }
+        );
+    }
+
+    fn ring(n: usize, radius: f64, z: f64, twist: f64) -> Vec<DVec3> {
+        (0..n)
+            .map(|i| {
+                let theta = std::f64::consts::TAU * i as f64 / n as f64 + twist;
+                DVec3::new(radius * theta.cos(), radius * theta.sin(), z)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prism_from_rings() {
+        let (bottom, top) = (ring(6, 16.0, 0.0, 0.0), ring(6, 8.0, 32.0, 0.0));
+        let frustum = prism(&bottom, &top).unwrap();
+        let hull = convex_hull(&[bottom.clone(), top.clone()].concat(), None).unwrap();
+        assert!((frustum.volume() - hull.volume()).abs() < 1e-9);
+        assert_eq!(frustum.to_sides_unique().len(), 8);
+        assert!(outward(&frustum));
+        // The first side spans the first two points of each ring.
+        let (vertices, sides) = frustum.side_vertex_indices();
+        let first = sides[0].0.map(|i| vertices[i]);
+        for point in [bottom[0], bottom[1], top[1]] {
+            assert!(first.contains(&point));
+        }
+
+        // The same rings listed the other way around.
+        let reversed: Vec<Vec<DVec3>> = [&bottom, &top]
+            .iter()
+            .map(|ring| ring.iter().rev().copied().collect())
+            .collect();
+        let frustum = prism(&reversed[0], &reversed[1]).unwrap();
+        assert!((frustum.volume() - hull.volume()).abs() < 1e-9);
+        assert!(outward(&frustum));
+    }
+
+    #[test]
+    fn prism_rejects_twisted_rings() {
+        let bottom = ring(6, 16.0, 0.0, 0.0);
+        // Twisted ends bend every side.
+        let twisted = ring(6, 16.0, 32.0, 0.3);
+        assert!(prism(&bottom, &twisted).is_none());
+        // Points out of order around the ring.
+        let mut shuffled = ring(6, 16.0, 32.0, 0.0);
+        shuffled.swap(1, 2);
+        assert!(prism(&bottom, &shuffled).is_none());
+        // An end that is not convex.
+        let mut dent = ring(6, 16.0, 32.0, 0.0);
+        dent[0] = DVec3::new(4.0, 0.0, 32.0);
+        assert!(prism(&bottom, &dent).is_none());
+
+        let brush = Brush::try_from_rings(&bottom, &twisted, None).unwrap();
+        assert_eq!(brush.vertices().len(), 12);
+        assert!(outward(&brush));
+    }
+
+    #[test]
+    fn hull_errors_name_vertices() {
+        assert_eq!(
+            convex_hull(&cube()[..3], None).unwrap_err(),
+            HullError::NotEnoughVertices { count: 3 }
+        );
+
+        let mut points = cube();
+        points[5].y = f64::NAN;
+        assert!(matches!(
+            convex_hull(&points, None).unwrap_err(),
+            HullError::NonFinite { index: 5, .. }
+        ));
+
+        assert_eq!(
+            convex_hull(&[DVec3::ONE; 5], None).unwrap_err(),
+            HullError::Coincident { index: 0 }
+        );
+
+        let line: Vec<DVec3> = (0..5).map(|i| DVec3::splat(i as f64)).collect();
+        assert_eq!(
+            convex_hull(&line, None).unwrap_err(),
+            HullError::Collinear { a: 0, b: 4 }
+        );
+
+        // The bottom of the cube, with one point lifted by less than the tolerance.
+        let mut square = cube()[..4].to_vec();
+        square[3].z = 1e-12;
+        square.push(DVec3::new(0.5, 0.5, 0.0));
+        assert!(matches!(
+            convex_hull(&square, None).unwrap_err(),
+            HullError::Coplanar { .. }
+        ));
+
+        // A lone triangle has nothing on the other side of its edges.
+        let points = cube();
+        let mut hull = Hull {
+            points: &points,
+            epsilon: epsilon(&points),
+            faces: Vec::new(),
+            edges: HashMap::new(),
+        };
+        hull.add_face([0, 1, 2]);
+        assert_eq!(
+            hull.neighbour((0, 1)),
+            Err(HullError::OpenEdge { a: 0, b: 1 })
+        );
+
+        // Back to back, the two faces of a flat triangle make one side with nothing around it.
+        hull.add_face([0, 2, 1]);
+        assert_eq!(
+            to_brush(&hull).unwrap_err(),
+            HullError::NoBoundary { a: 0, b: 1, c: 2 }
+        );
+    }
+}
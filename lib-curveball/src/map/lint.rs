@@ -0,0 +1,350 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Checking brushes for problems that trouble map compilers and editors.
+
+use crate::map::geometry::{Brush, SideGeom, triangle_area};
+use glam::DVec3;
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Thresholds used by [lint].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LintOptions {
+    /// Brushes enclosing less than this volume are reported.
+    pub min_volume: f64,
+    /// Faces narrower than this are reported. A face's width is its area divided by its longest
+    /// span.
+    pub min_face_width: f64,
+    /// Edges shorter than this are reported.
+    pub min_edge_length: f64,
+    /// Sides whose three points meet at an angle with a sine smaller than this are reported.
+    pub collinear_tolerance: f64,
+    /// If set, vertices further than `off_grid_tolerance` from a point on a grid with this spacing
+    /// are reported.
+    pub grid: Option<f64>,
+    pub off_grid_tolerance: f64,
+    /// Vertices with a coordinate outside `-extent..=extent` are reported.
+    pub extent: f64,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            min_volume: 0.001,
+            min_face_width: 0.01,
+            min_edge_length: 0.01,
+            collinear_tolerance: 0.0001,
+            grid: None,
+            off_grid_tolerance: 0.001,
+            extent: 65536.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The map is unlikely to compile or play correctly.
+    Error,
+    /// The map should work but may look wrong or be awkward to edit.
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "Error"),
+            Self::Warning => write!(f, "Warning"),
+        }
+    }
+}
+
+/// A problem found by [lint].
+///
+/// `brush` is an index into the brushes given to [lint]. `side` is an index into the brush's
+/// [to_sides_unique](Brush::to_sides_unique), the sides written to a map, while `triangle` and
+/// `kept` are indices into its [triangles](Brush::triangles).
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    #[error("Brush {brush}: only {faces} distinct faces, at least 4 are needed")]
+    Degenerate { brush: usize, faces: usize },
+    #[error("Brush {brush}: volume {volume} is too small")]
+    ZeroVolume { brush: usize, volume: f64 },
+    #[error("Brush {brush}: side {side} is a sliver {width} units wide")]
+    SliverFace {
+        brush: usize,
+        side: usize,
+        width: f64,
+    },
+    #[error("Brush {brush}: edge from {from} to {to} is too short")]
+    ShortEdge {
+        brush: usize,
+        from: DVec3,
+        to: DVec3,
+    },
+    #[error("Brush {brush}: vertex {vertex} is off the grid")]
+    OffGrid { brush: usize, vertex: DVec3 },
+    #[error("Brush {brush}: the points defining side {side} are nearly collinear")]
+    NearlyCollinear { brush: usize, side: usize },
+    #[error("Brush {brush}: vertex {vertex} is outside the map's extents")]
+    OutOfBounds { brush: usize, vertex: DVec3 },
+    #[error(
+        "Brush {brush}: triangle {triangle} is merged into triangle {kept} and its material is lost"
+    )]
+    MergedSide {
+        brush: usize,
+        triangle: usize,
+        kept: usize,
+    },
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Degenerate { .. }
+            | Self::ZeroVolume { .. }
+            | Self::NearlyCollinear { .. }
+            | Self::OutOfBounds { .. } => Severity::Error,
+            Self::SliverFace { .. }
+            | Self::ShortEdge { .. }
+            | Self::OffGrid { .. }
+            | Self::MergedSide { .. } => Severity::Warning,
+        }
+    }
+
+    pub fn brush(&self) -> usize {
+        match *self {
+            Self::Degenerate { brush, .. }
+            | Self::ZeroVolume { brush, .. }
+            | Self::SliverFace { brush, .. }
+            | Self::ShortEdge { brush, .. }
+            | Self::OffGrid { brush, .. }
+            | Self::NearlyCollinear { brush, .. }
+            | Self::OutOfBounds { brush, .. }
+            | Self::MergedSide { brush, .. } => brush,
+        }
+    }
+}
+
+/// Check each brush for problems, returning diagnostics in brush order.
+///
+/// A brush with fewer than four distinct faces is only reported as
+/// [Degenerate](Diagnostic::Degenerate), since every other check would fail on it too.
+pub fn lint(brushes: &[Brush], options: &LintOptions) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (brush, b) in brushes.iter().enumerate() {
+        lint_brush(brush, b, options, &mut diagnostics);
+    }
+    diagnostics
+}
+
+fn lint_brush(brush: usize, b: &Brush, options: &LintOptions, out: &mut Vec<Diagnostic>) {
+    let (vertices, sides) = b.side_vertex_indices();
+    let triangles: Vec<SideGeom> = b.triangles().map(|side| side.geom).collect();
+
+    // The first equivalent triangle is the one to_sides_unique keeps.
+    let kept: Vec<usize> = (0..triangles.len())
+        .map(|i| {
+            (0..i)
+                .find(|&j| triangles[j].equivalent(triangles[i]))
+                .unwrap_or(i)
+        })
+        .collect();
+    let unique: Vec<usize> = (0..triangles.len()).filter(|&i| kept[i] == i).collect();
+
+    if unique.len() < 4 {
+        out.push(Diagnostic::Degenerate {
+            brush,
+            faces: unique.len(),
+        });
+        return;
+    }
+
+    let volume = b.volume();
+    if volume < options.min_volume {
+        out.push(Diagnostic::ZeroVolume { brush, volume });
+    }
+
+    for (side, &first) in unique.iter().enumerate() {
+        let face: Vec<usize> = (0..triangles.len()).filter(|&i| kept[i] == first).collect();
+        let area: f64 = face.iter().map(|&i| triangle_area(triangles[i])).sum();
+        let points: Vec<DVec3> = face.iter().flat_map(|&i| triangles[i].0).collect();
+        let span = points
+            .iter()
+            .flat_map(|a| points.iter().map(|b| a.distance(*b)))
+            .fold(0.0, f64::max);
+        let width = if span > 0.0 { area / span } else { 0.0 };
+        if width < options.min_face_width {
+            out.push(Diagnostic::SliverFace { brush, side, width });
+        }
+
+        let SideGeom([p0, p1, p2]) = triangles[first];
+        let (a, c) = (p0 - p1, p2 - p1);
+        let lengths = a.length() * c.length();
+        if lengths == 0.0 || a.cross(c).length() / lengths < options.collinear_tolerance {
+            out.push(Diagnostic::NearlyCollinear { brush, side });
+        }
+    }
+
+    let mut edges = HashSet::new();
+    for (indices, _) in sides {
+        for k in 0..3 {
+            let (i, j) = (indices[k], indices[(k + 1) % 3]);
+            if edges.insert((i.min(j), i.max(j)))
+                && vertices[i].distance(vertices[j]) < options.min_edge_length
+            {
+                out.push(Diagnostic::ShortEdge {
+                    brush,
+                    from: vertices[i.min(j)],
+                    to: vertices[i.max(j)],
+                });
+            }
+        }
+    }
+
+    for &vertex in vertices {
+        if let Some(grid) = options.grid
+            && vertex.distance((vertex / grid).round() * grid) > options.off_grid_tolerance
+        {
+            out.push(Diagnostic::OffGrid { brush, vertex });
+        }
+        if vertex.abs().max_element() > options.extent {
+            out.push(Diagnostic::OutOfBounds { brush, vertex });
+        }
+    }
+
+    for (triangle, &kept) in kept.iter().enumerate() {
+        if kept != triangle && sides[triangle].1 != sides[kept].1 {
+            out.push(Diagnostic::MergedSide {
+                brush,
+                triangle,
+                kept,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Curve;
+    use crate::curve::curve_classic::CurveClassic;
+    use crate::map::geometry::SideMtrl;
+
+    fn cuboid(min: DVec3, max: DVec3) -> Brush {
+        let vertices: Vec<DVec3> = (0..8)
+            .map(|i| {
+                DVec3::select(
+                    glam::BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0),
+                    max,
+                    min,
+                )
+            })
+            .collect();
+        Brush::try_from_vertices(&vertices, None).unwrap()
+    }
+
+    #[test]
+    fn lint_clean_curve() {
+        let brushes = CurveClassic {
+            n: 8,
+            ri0: 32.0,
+            ro0: 64.0,
+            ri1: 32.0,
+            ro1: 64.0,
+            theta0: 0.0,
+            theta1: 90.0,
+            t: 8.0,
+        }
+        .bake()
+        .unwrap();
+        assert_eq!(lint(&brushes, &LintOptions::default()), vec![]);
+    }
+
+    #[test]
+    fn lint_cuboid_volume() {
+        let brushes = [cuboid(DVec3::ZERO, DVec3::new(2.0, 3.0, 4.0))];
+        let options = LintOptions {
+            min_volume: 25.0,
+            ..Default::default()
+        };
+        let diagnostics = lint(&brushes, &options);
+        assert_eq!(diagnostics.len(), 1);
+        let Diagnostic::ZeroVolume { brush: 0, volume } = diagnostics[0] else {
+            panic!("{:?}", diagnostics);
+        };
+        assert!((volume - 24.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lint_sliver_and_short_edge() {
+        let brushes = [
+            cuboid(DVec3::ZERO, DVec3::ONE),
+            cuboid(DVec3::ZERO, DVec3::new(16.0, 16.0, 0.005)),
+        ];
+        let diagnostics = lint(&brushes, &LintOptions::default());
+        assert!(diagnostics.iter().all(|d| d.brush() == 1));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| matches!(d, Diagnostic::SliverFace { .. }))
+                .count(),
+            4
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| matches!(d, Diagnostic::ShortEdge { .. }))
+                .count(),
+            4
+        );
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| d.severity() == Severity::Warning)
+        );
+    }
+
+    #[test]
+    fn lint_grid_and_extents() {
+        let brushes = [cuboid(
+            DVec3::new(0.5, 0.0, 0.0),
+            DVec3::new(8.0, 8.0, 70000.0),
+        )];
+        let options = LintOptions {
+            grid: Some(1.0),
+            ..Default::default()
+        };
+        let diagnostics = lint(&brushes, &options);
+        let count = |f: fn(&Diagnostic) -> bool| diagnostics.iter().filter(|d| f(d)).count();
+        assert_eq!(count(|d| matches!(d, Diagnostic::OffGrid { .. })), 4);
+        assert_eq!(count(|d| matches!(d, Diagnostic::OutOfBounds { .. })), 4);
+        assert_eq!(diagnostics.len(), 8);
+    }
+
+    #[test]
+    fn lint_merged_side() {
+        let mut brush = cuboid(DVec3::ZERO, DVec3::ONE);
+        assert_eq!(lint(&[brush.clone()], &LintOptions::default()), vec![]);
+
+        // Give one triangle of a square face a different material.
+        let top = brush
+            .triangles()
+            .position(|side| side.geom.normal().unwrap().z > 0.5)
+            .unwrap();
+        let other = brush
+            .triangles()
+            .enumerate()
+            .position(|(i, side)| i > top && side.geom.normal().unwrap().z > 0.5)
+            .unwrap();
+        brush.sides[other].1 = SideMtrl::new("mtrl/turf-grey");
+        assert_eq!(
+            lint(&[brush], &LintOptions::default()),
+            vec![Diagnostic::MergedSide {
+                brush: 0,
+                triangle: other,
+                kept: top,
+            }]
+        );
+    }
+}
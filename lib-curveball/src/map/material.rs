@@ -0,0 +1,222 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Assigning materials to the faces of a curve according to the role each face plays.
+
+use crate::map::geometry::{Brush, SideMtrl};
+use std::collections::HashMap;
+
+/// The part of a curve a face belongs to.
+///
+/// Curve generators tag the sides of the brushes they produce with a role. Sides inside the curve,
+/// where one segment meets the next, have no role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SurfaceRole {
+    Top,
+    Bottom,
+    InnerWall,
+    OuterWall,
+    StartCap,
+    EndCap,
+}
+
+impl std::fmt::Display for SurfaceRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Top => write!(f, "Top"),
+            Self::Bottom => write!(f, "Bottom"),
+            Self::InnerWall => write!(f, "Inner wall"),
+            Self::OuterWall => write!(f, "Outer wall"),
+            Self::StartCap => write!(f, "Start cap"),
+            Self::EndCap => write!(f, "End cap"),
+        }
+    }
+}
+
+/// A mapping from [SurfaceRole] to the material faces with that role should receive.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MaterialMap {
+    materials: HashMap<SurfaceRole, SideMtrl>,
+}
+
+impl MaterialMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, role: SurfaceRole, mtrl: SideMtrl) -> Self {
+        self.materials.insert(role, mtrl);
+        self
+    }
+
+    pub fn get(&self, role: SurfaceRole) -> Option<&SideMtrl> {
+        self.materials.get(&role)
+    }
+
+    /// Replace the material of every side whose role appears in this map. The side keeps its
+    /// role; sides with no role, or a role missing from the map, are left alone.
+    pub fn apply<'a>(&self, brushes: impl IntoIterator<Item = &'a mut Brush>) {
+        for brush in brushes {
+            for mtrl in brush.materials_mut() {
+                let Some(role) = mtrl.role else {
+                    continue;
+                };
+                if let Some(new_mtrl) = self.get(role) {
+                    *mtrl = SideMtrl {
+                        role: Some(role),
+                        ..new_mtrl.clone()
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Curve;
+    use crate::curve::curve_classic::CurveClassic;
+    use glam::DVec3;
+
+    fn roles(brush: &Brush) -> Vec<Option<SurfaceRole>> {
+        let mut roles: Vec<_> = brush
+            .to_sides_unique()
+            .into_iter()
+            .map(|side| side.mtrl.role)
+            .collect();
+        roles.sort();
+        roles
+    }
+
+    #[test]
+    fn curve_classic_roles() {
+        let curve = CurveClassic {
+            n: 3,
+            ri0: 32.0,
+            ro0: 64.0,
+            ri1: 32.0,
+            ro1: 64.0,
+            theta0: 0.0,
+            theta1: 90.0,
+            t: 8.0,
+        };
+        let brushes = curve.bake().unwrap();
+
+        use SurfaceRole as SR;
+        assert_eq!(
+            roles(&brushes[0]),
+            [
+                None,
+                Some(SR::Top),
+                Some(SR::Bottom),
+                Some(SR::InnerWall),
+                Some(SR::OuterWall),
+                Some(SR::StartCap)
+            ]
+        );
+        assert_eq!(
+            roles(&brushes[1]),
+            [
+                None,
+                None,
+                Some(SR::Top),
+                Some(SR::Bottom),
+                Some(SR::InnerWall),
+                Some(SR::OuterWall)
+            ]
+        );
+        assert_eq!(
+            roles(&brushes[2]),
+            [
+                None,
+                Some(SR::Top),
+                Some(SR::Bottom),
+                Some(SR::InnerWall),
+                Some(SR::OuterWall),
+                Some(SR::EndCap)
+            ]
+        );
+
+        // The top face of a flat curve faces up.
+        for side in brushes[1].triangles() {
+            if side.mtrl.role == Some(SR::Top) {
+                assert!(side.geom.normal().unwrap().z > 0.99);
+            }
+        }
+    }
+
+    #[test]
+    fn extrude_roles() {
+        use crate::curve::extrude::path::Revolve;
+        use crate::curve::extrude::profile::{Anchor9Point, Rectangle};
+        use crate::curve::extrude::{ProfileOrientation, extrude};
+
+        let profile = Rectangle::new(32.0, 8.0, Anchor9Point::Center).unwrap();
+        let path = Revolve::new(128.0, 0.0, 90.0);
+        let brushes = extrude(4, &profile, &path, ProfileOrientation::FollowPath).unwrap();
+
+        use SurfaceRole as SR;
+        let expected_normal = |role: SR, point: DVec3| match role {
+            SR::Top => DVec3::Z,
+            SR::Bottom => DVec3::NEG_Z,
+            SR::OuterWall => point.with_z(0.0).normalize(),
+            SR::InnerWall => -point.with_z(0.0).normalize(),
+            SR::StartCap => DVec3::NEG_Y,
+            SR::EndCap => DVec3::NEG_X,
+        };
+        for (i, brush) in brushes.iter().enumerate() {
+            let sides = brush.to_sides_unique();
+            assert_eq!(sides.len(), 6);
+            for side in sides {
+                let Some(role) = side.mtrl.role else {
+                    continue;
+                };
+                let normal = side.geom.normal().unwrap();
+                let point = side.geom.0.iter().sum::<DVec3>() / 3.0;
+                assert!(normal.dot(expected_normal(role, point)) > 0.9);
+            }
+            let caps = brush
+                .to_sides_unique()
+                .iter()
+                .filter(|side| matches!(side.mtrl.role, Some(SR::StartCap | SR::EndCap)))
+                .count();
+            assert_eq!(caps, if i == 0 || i == 3 { 1 } else { 0 });
+        }
+    }
+
+    #[test]
+    fn apply_materials() {
+        let curve = CurveClassic {
+            n: 2,
+            ri0: 32.0,
+            ro0: 64.0,
+            ri1: 32.0,
+            ro1: 64.0,
+            theta0: 0.0,
+            theta1: 90.0,
+            t: 8.0,
+        };
+        let mut brushes = curve.bake().unwrap();
+        let materials = MaterialMap::new().with(SurfaceRole::Top, SideMtrl::new("mtrl/turf"));
+        materials.apply(&mut brushes);
+
+        for side in brushes.iter().flat_map(|brush| brush.to_sides_unique()) {
+            if side.mtrl.role == Some(SurfaceRole::Top) {
+                assert_eq!(side.mtrl.texture, "mtrl/turf");
+            } else {
+                assert_eq!(side.mtrl, SideMtrl::default());
+            }
+        }
+    }
+
+    #[test]
+    fn roles_do_not_affect_equality() {
+        let top = SideMtrl {
+            role: Some(SurfaceRole::Top),
+            ..SideMtrl::new("mtrl/turf")
+        };
+        assert_eq!(top, SideMtrl::new("mtrl/turf"));
+        assert_ne!(top, SideMtrl::default());
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Merging neighbouring brushes to reduce the number of brushes in a map.
+
+use crate::map::geometry::{Aabb, Brush, Plane, SideMtrl};
+use crate::map::hull::MAX_HULL_ITER;
+use glam::DVec3;
+use std::collections::BTreeSet;
+
+/// Relative difference allowed between the volume of a merged brush and the brushes it replaces.
+const VOLUME_EPSILON: f64 = 1e-6;
+
+/// Merge neighbouring brushes whose union is still a convex brush, returning the remaining brushes
+/// and the number of merges made.
+///
+/// Two brushes are merged when they touch along a shared plane, the convex hull of their vertices
+/// has the same volume as the two brushes together, and every side of the hull lies on sides of
+/// the originals that all have the same material. Merged brushes take the place of the first of
+/// the two, so the order of the brushes is otherwise kept.
+pub fn merge_brushes(brushes: Vec<Brush>) -> (Vec<Brush>, usize) {
+    let mut slots: Vec<Option<(Brush, Aabb)>> = brushes
+        .into_iter()
+        .map(|brush| {
+            let bounds = brush.bounds();
+            Some((brush, bounds))
+        })
+        .collect();
+    let touching = |slots: &[Option<(Brush, Aabb)>], start: usize, bounds: Aabb| {
+        (start..slots.len())
+            .filter(|&j| matches!(&slots[j], Some((_, other)) if other.touches(bounds)))
+            .collect::<BTreeSet<usize>>()
+    };
+
+    let mut merged = 0;
+    for i in 0..slots.len() {
+        let Some((mut brush, mut bounds)) = slots[i].take() else {
+            continue;
+        };
+        // Only brushes whose bounds touch can share a side, so the rest are never tried.
+        let mut pending = touching(&slots, i + 1, bounds);
+        while let Some(j) = pending.pop_first() {
+            let Some((other, other_bounds)) = &slots[j] else {
+                continue;
+            };
+            if let Some(union) = try_merge(&brush, other) {
+                let other_bounds = *other_bounds;
+                slots[j] = None;
+                brush = union;
+                bounds = bounds.union(other_bounds);
+                merged += 1;
+                // Brushes already tried may merge now if they touch the part just added.
+                pending.extend(touching(&slots, i + 1, other_bounds));
+            }
+        }
+        slots[i] = Some((brush, bounds));
+    }
+    (
+        slots
+            .into_iter()
+            .flatten()
+            .map(|(brush, _)| brush)
+            .collect(),
+        merged,
+    )
+}
+
+/// Merge two brushes if their union is convex and no material would be lost.
+fn try_merge(a: &Brush, b: &Brush) -> Option<Brush> {
+    let planes_a = planes(a);
+    let planes_b = planes(b);
+    let touching = planes_a.iter().any(|(plane, _)| {
+        planes_b
+            .iter()
+            .any(|(other, _)| plane.flipped().equivalent(*other))
+    });
+    if !touching {
+        return None;
+    }
+
+    let vertices: Vec<DVec3> = a.vertices.iter().chain(&b.vertices).copied().collect();
+    let mut hull = Brush::try_from_vertices(&vertices, MAX_HULL_ITER).ok()?;
+    let volume = a.volume() + b.volume();
+    if (hull.volume() - volume).abs() > VOLUME_EPSILON * volume.max(1.0) {
+        return None;
+    }
+
+    let mut lost = false;
+    hull.update_materials(|geom, mtrl| {
+        let mut originals = geom.plane().into_iter().flat_map(|plane| {
+            planes_a
+                .iter()
+                .chain(&planes_b)
+                .filter(move |(other, _)| plane.equivalent(*other))
+                .map(|(_, mtrl)| *mtrl)
+        });
+        match originals.next() {
+            Some(first) if originals.all(|other| other == first) => *mtrl = first.clone(),
+            _ => lost = true,
+        }
+    });
+    (!lost).then_some(hull)
+}
+
+fn planes(brush: &Brush) -> Vec<(Plane, &SideMtrl)> {
+    brush
+        .triangles()
+        .zip(&brush.sides)
+        .filter_map(|(side, (_, mtrl))| Some((side.geom.plane()?, mtrl)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::extrude::path::{Line, Revolve};
+    use crate::curve::extrude::profile::{Anchor9Point, Rectangle};
+    use crate::curve::extrude::{ProfileOrientation, ProfilePlane, extrude};
+
+    fn straight(n: u32) -> Vec<Brush> {
+        let profile = Rectangle::new(32.0, 8.0, Anchor9Point::Center).unwrap();
+        let path = Line::new(256.0, 0.0, 0.0);
+        extrude(
+            n,
+            &profile,
+            &path,
+            ProfileOrientation::Constant(ProfilePlane::YZ),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn merge_straight() {
+        let brushes = straight(8);
+        let volume: f64 = brushes.iter().map(Brush::volume).sum();
+        let (merged, count) = merge_brushes(brushes);
+        assert_eq!(count, 7);
+        assert_eq!(merged.len(), 1);
+        assert!((merged[0].volume() - volume).abs() < 1e-6);
+        assert_eq!(merged[0].to_sides_unique().len(), 6);
+    }
+
+    #[test]
+    fn merge_keeps_curves_and_materials() {
+        let profile = Rectangle::new(32.0, 8.0, Anchor9Point::Center).unwrap();
+        let path = Revolve::new(128.0, 0.0, 90.0);
+        let brushes = extrude(4, &profile, &path, ProfileOrientation::FollowPath).unwrap();
+        assert_eq!(merge_brushes(brushes).1, 0);
+
+        // A different texture on one segment stops it merging with its neighbours.
+        let mut brushes = straight(4);
+        for mtrl in brushes[2].materials_mut() {
+            mtrl.texture = "mtrl/turf-grey".to_string();
+        }
+        let (merged, count) = merge_brushes(brushes);
+        assert_eq!(count, 1);
+        assert_eq!(merged.len(), 3);
+    }
+
+    #[test]
+    fn merge_retries_after_growing() {
+        let cuboid = |min: DVec3, max: DVec3| {
+            let corners: Vec<DVec3> = (0..8)
+                .map(|i| {
+                    DVec3::select(
+                        glam::BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0),
+                        max,
+                        min,
+                    )
+                })
+                .collect();
+            Brush::try_from_vertices(&corners, None).unwrap()
+        };
+        // The first two form an L, which only becomes convex once the third fills the corner.
+        let brushes = vec![
+            cuboid(DVec3::ZERO, DVec3::ONE),
+            cuboid(DVec3::Y, DVec3::new(2.0, 2.0, 1.0)),
+            cuboid(DVec3::X, DVec3::new(2.0, 1.0, 1.0)),
+            cuboid(DVec3::splat(5.0), DVec3::splat(6.0)),
+        ];
+        let (merged, count) = merge_brushes(brushes);
+        assert_eq!(count, 2);
+        assert_eq!(merged.len(), 2);
+        assert!((merged[0].volume() - 4.0).abs() < 1e-9);
+        assert_eq!(merged[1].bounds().min, DVec3::splat(5.0));
+    }
+}
@@ -8,4 +8,20 @@ pub mod entity;
 
 pub mod geometry;
 
+pub mod hull;
+
+pub mod lint;
+
+pub mod material;
+
+pub mod merge;
+
+pub mod parse;
+
 pub mod qmap;
+
+pub mod snap;
+
+pub mod transform;
+
+pub mod writer;
@@ -0,0 +1,458 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A parser turning Quake3 `.map` text back into a [QMap].
+//!
+//! Brushes in a `.map` file are described by their planes. The parser rebuilds each [Brush] from
+//! those planes, so the text need not have been produced by this crate.
+
+use crate::map::geometry::{Brush, BrushError, Side, SideGeom, SideMtrl};
+use crate::map::qmap::{QEntity, QMap};
+use glam::DVec3;
+use std::collections::HashMap;
+use thiserror::Error;
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// An error encountered while parsing map text, along with where it happened.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("line {line}, column {column}: {kind}")]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    #[error("Unexpected end of file; expected {expected}")]
+    UnexpectedEof { expected: &'static str },
+    #[error("Unexpected \"{found}\"; expected {expected}")]
+    UnexpectedToken {
+        found: String,
+        expected: &'static str,
+    },
+    #[error("Unterminated quoted string")]
+    UnterminatedString,
+    #[error("\"{0}\" is not a valid number")]
+    InvalidNumber(String),
+    #[error("Unsupported map primitive \"{0}\"")]
+    UnsupportedPrimitive(String),
+    #[error("Invalid brush: {0}")]
+    InvalidBrush(BrushError),
+}
+
+/// Parse Quake3 map text into a [QMap].
+///
+/// Comments before the first entity become the map's metadata, apart from the `// entity N`
+/// markers written by [QMap::bake].
+pub fn parse_map(text: &str) -> ParseResult<QMap> {
+    let mut parser = Parser::new(text);
+    let mut entities = Vec::new();
+    while parser.peek()?.is_some() {
+        entities.push(parser.entity()?);
+    }
+    Ok(QMap {
+        entities,
+        metadata: parser.metadata,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    OpenBrace,
+    CloseBrace,
+    OpenParen,
+    CloseParen,
+    Quoted(String),
+    Word(String),
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OpenBrace => write!(f, "{{"),
+            Self::CloseBrace => write!(f, "}}"),
+            Self::OpenParen => write!(f, "("),
+            Self::CloseParen => write!(f, ")"),
+            Self::Quoted(s) => write!(f, "\"{s}\""),
+            Self::Word(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+    peeked: Option<Token>,
+    seen_entity: bool,
+    metadata: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            line: 1,
+            column: 1,
+            peeked: None,
+            seen_entity: false,
+            metadata: Vec::new(),
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn lex(&mut self) -> ParseResult<Option<Token>> {
+        loop {
+            while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+                self.bump();
+            }
+            let (line, column) = (self.line, self.column);
+            let Some(c) = self.bump() else {
+                return Ok(None);
+            };
+            let kind = match c {
+                '{' => TokenKind::OpenBrace,
+                '}' => TokenKind::CloseBrace,
+                '(' => TokenKind::OpenParen,
+                ')' => TokenKind::CloseParen,
+                '"' => {
+                    let mut s = String::new();
+                    loop {
+                        match self.bump() {
+                            Some('"') => break,
+                            Some('\n') | None => {
+                                return Err(ParseError {
+                                    line,
+                                    column,
+                                    kind: ParseErrorKind::UnterminatedString,
+                                });
+                            }
+                            Some(c) => s.push(c),
+                        }
+                    }
+                    TokenKind::Quoted(s)
+                }
+                '/' if self.chars.peek() == Some(&'/') => {
+                    self.bump();
+                    let mut comment = String::new();
+                    while let Some(c) = self.chars.peek().copied() {
+                        if c == '\n' {
+                            break;
+                        }
+                        comment.push(c);
+                        self.bump();
+                    }
+                    self.comment(comment.trim());
+                    continue;
+                }
+                c => {
+                    let mut s = String::from(c);
+                    while let Some(c) = self.chars.peek().copied() {
+                        if c.is_whitespace() || matches!(c, '{' | '}' | '(' | ')' | '"') {
+                            break;
+                        }
+                        s.push(c);
+                        self.bump();
+                    }
+                    TokenKind::Word(s)
+                }
+            };
+            return Ok(Some(Token { kind, line, column }));
+        }
+    }
+
+    fn comment(&mut self, comment: &str) {
+        if self.seen_entity {
+            return;
+        }
+        let is_entity_marker = comment
+            .strip_prefix("entity ")
+            .is_some_and(|n| n.parse::<usize>().is_ok());
+        if !is_entity_marker {
+            self.metadata.push(comment.to_string());
+        }
+    }
+
+    fn peek(&mut self) -> ParseResult<Option<&Token>> {
+        if self.peeked.is_none() {
+            self.peeked = self.lex()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    fn next(&mut self, expected: &'static str) -> ParseResult<Token> {
+        let token = match self.peeked.take() {
+            Some(token) => Some(token),
+            None => self.lex()?,
+        };
+        token.ok_or_else(|| self.error(ParseErrorKind::UnexpectedEof { expected }))
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &'static str) -> ParseResult<Token> {
+        let token = self.next(expected)?;
+        if token.kind != kind {
+            return Err(unexpected(token, expected));
+        }
+        Ok(token)
+    }
+
+    fn word(&mut self, expected: &'static str) -> ParseResult<Token> {
+        let token = self.next(expected)?;
+        match token.kind {
+            TokenKind::Word(_) => Ok(token),
+            _ => Err(unexpected(token, expected)),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, expected: &'static str) -> ParseResult<T> {
+        let token = self.word(expected)?;
+        let TokenKind::Word(s) = token.kind else {
+            unreachable!()
+        };
+        s.parse().map_err(|_| ParseError {
+            line: token.line,
+            column: token.column,
+            kind: ParseErrorKind::InvalidNumber(s),
+        })
+    }
+
+    fn entity(&mut self) -> ParseResult<QEntity> {
+        self.expect(TokenKind::OpenBrace, "\"{\" to start an entity")?;
+        self.seen_entity = true;
+        let mut parameters = HashMap::new();
+        let mut brushes = Vec::new();
+        loop {
+            let token = self.next("a key, a brush, or \"}\"")?;
+            match token.kind {
+                TokenKind::CloseBrace => break,
+                TokenKind::Quoted(key) => {
+                    let value = self.next("a quoted value")?;
+                    let TokenKind::Quoted(value) = value.kind else {
+                        return Err(unexpected(value, "a quoted value"));
+                    };
+                    parameters.insert(key, value);
+                }
+                TokenKind::OpenBrace => brushes.push(self.brush(token.line, token.column)?),
+                _ => return Err(unexpected(token, "a key, a brush, or \"}\"")),
+            }
+        }
+        Ok(QEntity {
+            parameters,
+            brushes,
+        })
+    }
+
+    fn brush(&mut self, line: usize, column: usize) -> ParseResult<Brush> {
+        let mut sides = Vec::new();
+        loop {
+            let token = self.next("a brush side or \"}\"")?;
+            match token.kind {
+                TokenKind::CloseBrace => break,
+                TokenKind::OpenParen => sides.push(self.side()?),
+                TokenKind::Word(word) => {
+                    return Err(ParseError {
+                        line: token.line,
+                        column: token.column,
+                        kind: ParseErrorKind::UnsupportedPrimitive(word),
+                    });
+                }
+                _ => return Err(unexpected(token, "a brush side or \"}\"")),
+            }
+        }
+        Brush::try_from_sides(&sides).map_err(|err| ParseError {
+            line,
+            column,
+            kind: ParseErrorKind::InvalidBrush(err),
+        })
+    }
+
+    // Parse a side, with the opening parenthesis of the first point already consumed.
+    fn side(&mut self) -> ParseResult<Side> {
+        let mut points = [DVec3::ZERO; 3];
+        for (i, point) in points.iter_mut().enumerate() {
+            if i > 0 {
+                self.expect(TokenKind::OpenParen, "\"(\"")?;
+            }
+            for component in 0..3 {
+                point[component] = self.number("a coordinate")?;
+            }
+            self.expect(TokenKind::CloseParen, "\")\"")?;
+        }
+
+        let texture = self.word("a texture name")?;
+        let TokenKind::Word(texture) = texture.kind else {
+            unreachable!()
+        };
+
+        let mut mtrl = SideMtrl {
+            offset_x: self.number("a texture offset")?,
+            offset_y: self.number("a texture offset")?,
+            rotation: self.number("a texture rotation")?,
+            scale_x: self.number("a texture scale")?,
+            scale_y: self.number("a texture scale")?,
+            ..SideMtrl::new(texture)
+        };
+        // Content flags, surface flags, and value are optional.
+        if matches!(
+            self.peek()?,
+            Some(Token {
+                kind: TokenKind::Word(_),
+                ..
+            })
+        ) {
+            mtrl.content_flags = self.number("content flags")?;
+            mtrl.surface_flags = self.number("surface flags")?;
+            mtrl.value = self.number("a surface value")?;
+        }
+
+        Ok(Side {
+            geom: SideGeom(points),
+            mtrl,
+        })
+    }
+}
+
+fn unexpected(token: Token, expected: &'static str) -> ParseError {
+    ParseError {
+        line: token.line,
+        column: token.column,
+        kind: ParseErrorKind::UnexpectedToken {
+            found: token.kind.to_string(),
+            expected,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::entity::SimpleWorldspawn;
+
+    fn cube() -> Brush {
+        let vertices: Vec<DVec3> = (0..8)
+            .map(|i| DVec3 {
+                x: (i & 1) as f64 * 64.0,
+                y: ((i >> 1) & 1) as f64 * 64.0,
+                z: ((i >> 2) & 1) as f64 * 64.0,
+            })
+            .collect();
+        Brush::try_from_vertices(&vertices, Some(1000)).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let entity: QEntity = SimpleWorldspawn::new(vec![cube(), cube()]).into();
+        let map = QMap::new(vec![entity]).with_tb_neverball_metadata();
+        let text = map.to_string();
+
+        let parsed = parse_map(&text).unwrap();
+        assert_eq!(parsed.metadata, map.metadata);
+        assert_eq!(parsed.entities.len(), 1);
+        assert_eq!(
+            parsed.entities[0].parameters.get("classname").unwrap(),
+            "worldspawn"
+        );
+        assert_eq!(parsed.entities[0].brushes.len(), 2);
+
+        let brush = &parsed.entities[0].brushes[0];
+        assert_eq!(brush.vertices().len(), 8);
+        assert_eq!(brush.to_sides_unique().len(), 6);
+        for vertex in cube().vertices() {
+            assert!(brush.vertices().iter().any(|v| v.distance(*vertex) < 1e-6));
+        }
+    }
+
+    #[test]
+    fn parse_textures() {
+        let text = r#"{
+"classname" "worldspawn"
+{
+( 0 0 0 ) ( 0 1 0 ) ( 0 0 1 ) mtrl/a 0 0 0 0.5 0.5 0 0 0
+( 0 1 0 ) ( 1 0 0 ) ( 0 0 1 ) mtrl/b 0 0 0 0.5 0.5
+( 1 0 0 ) ( 0 0 0 ) ( 0 0 1 ) mtrl/c 0 0 0 0.5 0.5 0 0 0
+( 0 0 0 ) ( 1 0 0 ) ( 0 1 0 ) mtrl/d 16 -8 45 0.25 1 1 2 3
+}
+}
+"#;
+        let map = parse_map(text).unwrap();
+        let brush = &map.entities[0].brushes[0];
+        assert_eq!(brush.vertices().len(), 4);
+        let mut textures: Vec<String> = brush
+            .to_sides_unique()
+            .into_iter()
+            .map(|side| side.mtrl.texture)
+            .collect();
+        textures.sort();
+        assert_eq!(textures, ["mtrl/a", "mtrl/b", "mtrl/c", "mtrl/d"]);
+
+        let side = brush
+            .to_sides_unique()
+            .into_iter()
+            .find(|side| side.mtrl.texture == "mtrl/d")
+            .unwrap();
+        assert_eq!(
+            side.mtrl,
+            SideMtrl {
+                offset_x: 16.0,
+                offset_y: -8.0,
+                rotation: 45.0,
+                scale_x: 0.25,
+                scale_y: 1.0,
+                content_flags: 1,
+                surface_flags: 2,
+                value: 3,
+                ..SideMtrl::new("mtrl/d")
+            }
+        );
+    }
+
+    #[test]
+    fn error_location() {
+        let text = "{\n\"classname\" \"worldspawn\"\n{\n( 0 0 0 ) ( 0 1 0 ) ( 0 x 1 ) mtrl/a 0 0 0 0.5 0.5\n}\n}\n";
+        let err = parse_map(text).unwrap_err();
+        assert_eq!(err.line, 4);
+        assert_eq!(err.column, 25);
+        assert_eq!(err.kind, ParseErrorKind::InvalidNumber("x".to_string()));
+
+        let err = parse_map("{\n\"classname\" \"worldspawn\"\n").unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::UnexpectedEof { .. }));
+    }
+
+    #[test]
+    fn open_brush() {
+        let text = "{\n{\n( 0 0 0 ) ( 0 1 0 ) ( 0 0 1 ) mtrl/a 0 0 0 0.5 0.5\n}\n}\n";
+        let err = parse_map(text).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::InvalidBrush(BrushError::Unbounded)
+        );
+    }
+}
@@ -3,9 +3,14 @@
 
 //! Structs for Quake3 maps.
 
+use crate::map::entity::{TrenchbroomGroup, TrenchbroomLayer};
 use crate::map::geometry::Brush;
+use crate::map::parse::{ParseError, parse_map};
+use crate::map::writer::MapWriter;
 use core::fmt;
 use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+use std::str::FromStr;
 
 use std::collections::HashMap;
 
@@ -17,23 +22,27 @@ pub struct QEntity {
 }
 
 impl QEntity {
-    pub(crate) fn bake(&self) -> impl Display + use<'_> {
-        struct QEntityDisp<'a>(&'a QEntity);
+    pub(crate) fn bake(&self, writer: MapWriter) -> impl Display + use<'_> {
+        struct QEntityDisp<'a>(&'a QEntity, MapWriter);
         impl Display for QEntityDisp<'_> {
             fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-                writeln!(f, "{{",)?;
-                for (key, value) in self.0.parameters.iter() {
-                    writeln!(f, "\"{key}\" \"{value}\"")?;
+                let (entity, writer) = (self.0, self.1);
+                let eol = writer.line_ending;
+                write!(f, "{{{eol}")?;
+                for (key, value) in writer.ordered(&entity.parameters) {
+                    write!(f, "\"{key}\" \"{value}\"{eol}")?;
                 }
-                for (i, brush) in self.0.brushes.iter().enumerate() {
-                    writeln!(f, "// brush {i}")?;
-                    write!(f, "{}", brush.bake())?;
+                for (i, brush) in entity.brushes.iter().enumerate() {
+                    if writer.comments {
+                        write!(f, "// brush {i}{eol}")?;
+                    }
+                    write!(f, "{}", brush.bake(writer))?;
                 }
-                writeln!(f, "}}")?;
+                write!(f, "}}{eol}")?;
                 Ok(())
             }
         }
-        QEntityDisp(self)
+        QEntityDisp(self, writer)
     }
 }
 
@@ -55,6 +64,13 @@ impl QMap {
         }
     }
 
+    /// Add entities to the map, such as those produced by
+    /// [place_along](crate::curve::extrude::place::place_along).
+    pub fn with_entities(mut self, entities: impl IntoIterator<Item = QEntity>) -> Self {
+        self.entities.extend(entities);
+        self
+    }
+
     pub fn with_metadata(mut self, metadata: String) -> Self {
         self.metadata.push(metadata);
         self
@@ -65,21 +81,58 @@ impl QMap {
             .with_metadata("Format: Quake3".to_string())
     }
 
+    /// Add brushes to the map inside a new TrenchBroom group named `name`.
+    pub fn with_tb_group(mut self, name: impl Into<String>, brushes: Vec<Brush>) -> Self {
+        let group = TrenchbroomGroup::new(name, self.next_tb_id(), brushes);
+        self.entities.push(group.into());
+        self
+    }
+
+    /// Add brushes to the map inside a new TrenchBroom layer named `name`.
+    pub fn with_tb_layer(mut self, name: impl Into<String>, brushes: Vec<Brush>) -> Self {
+        let layer = TrenchbroomLayer::new(name, self.next_tb_id(), brushes);
+        self.entities.push(layer.into());
+        self
+    }
+
+    // TrenchBroom groups and layers share one set of ids.
+    fn next_tb_id(&self) -> u32 {
+        self.entities
+            .iter()
+            .filter_map(|entity| entity.parameters.get("_tb_id")?.parse::<u32>().ok())
+            .max()
+            .map_or(1, |id| id + 1)
+    }
+
     pub fn bake(&self) -> impl Display + use<'_> {
-        struct QMapDisp<'a>(&'a QMap);
+        self.bake_with(MapWriter::default())
+    }
+
+    /// Write the map as text to `out`, as it would be displayed.
+    ///
+    /// The text is written a piece at a time rather than being built up in memory first. `out`
+    /// receives many small writes, so consider wrapping it in a [BufWriter](std::io::BufWriter).
+    pub fn write_to(&self, out: impl Write) -> io::Result<()> {
+        MapWriter::default().write_to(self, out)
+    }
+
+    pub(crate) fn bake_with(&self, writer: MapWriter) -> impl Display + use<'_> {
+        struct QMapDisp<'a>(&'a QMap, MapWriter);
         impl Display for QMapDisp<'_> {
             fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-                for metadata_line in self.0.metadata.iter().flat_map(|meta| meta.lines()) {
-                    writeln!(f, "// {}", metadata_line)?;
-                }
-                for (i, entity) in self.0.entities.iter().enumerate() {
-                    writeln!(f, "// entity {i}")?;
-                    write!(f, "{}", entity.bake())?;
+                let (map, writer) = (self.0, self.1);
+                let eol = writer.line_ending;
+                write!(f, "{}", writer.header(&map.metadata))?;
+                for (i, entity) in map.entities.iter().enumerate() {
+                    if writer.comments {
+                        write!(f, "// entity {i}{eol}")?;
+                    }
+                    write!(f, "{}", entity.bake(writer))?;
                 }
                 Ok(())
             }
         }
-        QMapDisp(self)
+        QMapDisp(self, writer)
     }
 }
 
@@ -89,6 +142,15 @@ impl Display for QMap {
     }
 }
 
+impl FromStr for QMap {
+    type Err = ParseError;
+
+    /// Parse Quake3 map text. See [parse_map].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_map(s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,21 +183,37 @@ mod tests {
 "classname" "worldspawn"
 // brush 0
 {
-( 0.000000 0.000000 0.000000 ) ( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
-( 0.000000 1.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
 ( 1.000000 0.000000 0.000000 ) ( 0.000000 0.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
-( 0.000000 0.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) ( 0.000000 1.000000 0.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
+( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
+( 0.000000 1.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
+( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) ( 0.000000 0.000000 0.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
 }
 // brush 1
 {
-( 0.000000 0.000000 0.000000 ) ( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
-( 0.000000 1.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
 ( 1.000000 0.000000 0.000000 ) ( 0.000000 0.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
-( 0.000000 0.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) ( 0.000000 1.000000 0.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
+( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
+( 0.000000 1.000000 0.000000 ) ( 1.000000 0.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
+( 0.000000 1.000000 0.000000 ) ( 0.000000 0.000000 1.000000 ) ( 0.000000 0.000000 0.000000 ) mtrl/invisible 0 0 0 0.5 0.5 0 0 0
 }
 }
 "#;
         println!("{}", should_eq_str);
         assert_eq!(format!("{}", map.to_string()), should_eq_str);
     }
+
+    #[test]
+    fn tb_group_ids() {
+        let worldspawn = SimpleWorldspawn::new(Vec::new());
+        let map = QMap::new(vec![worldspawn.into()])
+            .with_tb_layer("Curves", Vec::new())
+            .with_tb_group("Curve 1", Vec::new())
+            .with_tb_group("Curve 2", Vec::new());
+
+        let ids: Vec<_> = map
+            .entities
+            .iter()
+            .filter_map(|entity| entity.parameters.get("_tb_id"))
+            .collect();
+        assert_eq!(ids, ["1", "2", "3"]);
+    }
 }
@@ -0,0 +1,256 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Snapping the vertices of brushes to a grid.
+
+use crate::map::geometry::{Brush, Plane, SideGeom};
+use glam::DVec3;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SnapError {
+    #[error("Grid size {0} must be greater than zero")]
+    InvalidGrid(f64),
+    #[error("Brush {brush}: side {side} is no longer flat after snapping")]
+    NonPlanar { brush: usize, side: usize },
+    #[error("Brush {brush}: side {side} collapses or turns inside out after snapping")]
+    Inverted { brush: usize, side: usize },
+    #[error("Brush {brush}: brush is no longer convex at side {side} after snapping")]
+    Concave { brush: usize, side: usize },
+}
+
+/// Move every vertex of every brush to the nearest point on a grid with spacing `grid`.
+///
+/// Vertices that are in the same place before snapping end up in the same place after, even
+/// across brushes, so brushes that touched still touch. Each brush keeps its sides, and every
+/// side's plane is found again from the snapped vertices. Sides left with no area are dropped.
+///
+/// Snapping to a grid too coarse for the brushes can bend a side or turn it inside out. If so, no
+/// brushes are returned; `side` in the error is the index of one of the brush's triangles.
+pub fn snap_to_grid(brushes: &[Brush], grid: f64) -> Result<Vec<Brush>, SnapError> {
+    if !grid.is_finite() || grid <= 0.0 {
+        return Err(SnapError::InvalidGrid(grid));
+    }
+    let mut weld = Weld::default();
+    brushes
+        .iter()
+        .enumerate()
+        .map(|(i, brush)| {
+            let vertices = brush
+                .vertices
+                .iter()
+                .map(|vertex| weld.snap(*vertex, grid))
+                .collect();
+            rebuild(brush, vertices).map_err(|fault| fault.for_brush(i))
+        })
+        .collect()
+}
+
+// Something wrong with a side of a brush, before it is known which brush.
+enum Fault {
+    NonPlanar(usize),
+    Inverted(usize),
+    Concave(usize),
+}
+
+impl Fault {
+    fn for_brush(self, brush: usize) -> SnapError {
+        match self {
+            Self::NonPlanar(side) => SnapError::NonPlanar { brush, side },
+            Self::Inverted(side) => SnapError::Inverted { brush, side },
+            Self::Concave(side) => SnapError::Concave { brush, side },
+        }
+    }
+}
+
+// Give a brush new vertices, checking each of its faces is still flat, facing the same way, and
+// part of a convex shape.
+fn rebuild(brush: &Brush, vertices: Vec<DVec3>) -> Result<Brush, Fault> {
+    let snapped = |side: usize| SideGeom(brush.sides[side].0.map(|idx| vertices[idx]));
+
+    // Group the triangles into faces by the plane they were on before snapping.
+    let mut faces: Vec<(Plane, Vec<usize>)> = Vec::new();
+    for (side, triangle) in brush.triangles().enumerate() {
+        let Some(plane) = triangle.geom.plane() else {
+            continue;
+        };
+        match faces.iter_mut().find(|(other, _)| other.equivalent(plane)) {
+            Some((_, sides)) => sides.push(side),
+            None => faces.push((plane, vec![side])),
+        }
+    }
+
+    for (plane, sides) in &faces {
+        let area = |side: usize| {
+            let SideGeom([p0, p1, p2]) = snapped(side);
+            (p0 - p1).cross(p2 - p1).length()
+        };
+        let largest = *sides
+            .iter()
+            .max_by(|a, b| area(**a).total_cmp(&area(**b)))
+            .unwrap();
+        let new_plane = snapped(largest)
+            .plane()
+            .filter(|new_plane| new_plane.normal.dot(plane.normal) > 0.0)
+            .ok_or(Fault::Inverted(largest))?;
+
+        for &side in sides {
+            let geom = snapped(side);
+            if geom
+                .0
+                .iter()
+                .any(|point| new_plane.distance_to(*point).abs() > SNAP_EPSILON)
+            {
+                return Err(Fault::NonPlanar(side));
+            }
+            if geom
+                .normal()
+                .is_some_and(|normal| normal.dot(new_plane.normal) < 0.0)
+            {
+                return Err(Fault::Inverted(side));
+            }
+        }
+        if vertices
+            .iter()
+            .any(|vertex| new_plane.distance_to(*vertex) > SNAP_EPSILON)
+        {
+            return Err(Fault::Concave(largest));
+        }
+    }
+
+    let sides = brush
+        .sides
+        .iter()
+        .enumerate()
+        .filter(|(side, _)| snapped(*side).normal().is_some())
+        .map(|(_, side)| side.clone())
+        .collect();
+    Ok(Brush { vertices, sides })
+}
+
+// Remembers where vertices were snapped to, so that vertices a rounding error apart still snap to
+// the same point even if they lie either side of the midpoint between two grid points.
+#[derive(Default)]
+struct Weld {
+    cells: HashMap<[i64; 3], Vec<(DVec3, DVec3)>>,
+}
+
+impl Weld {
+    fn snap(&mut self, vertex: DVec3, grid: f64) -> DVec3 {
+        let cell = (vertex / WELD_EPSILON).floor().as_i64vec3().to_array();
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let neighbor = [cell[0] + x, cell[1] + y, cell[2] + z];
+                    let found = self.cells.get(&neighbor).and_then(|welded| {
+                        welded
+                            .iter()
+                            .find(|(original, _)| original.distance(vertex) <= WELD_EPSILON)
+                    });
+                    if let Some((_, snapped)) = found {
+                        return *snapped;
+                    }
+                }
+            }
+        }
+
+        // Adding zero turns any negative zeros positive.
+        let snapped = (vertex / grid).round() * grid + DVec3::ZERO;
+        self.cells.entry(cell).or_default().push((vertex, snapped));
+        snapped
+    }
+}
+
+const WELD_EPSILON: f64 = 0.000001;
+
+const SNAP_EPSILON: f64 = 0.000001;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Curve;
+    use crate::curve::curve_classic::CurveClassic;
+
+    fn curve() -> Vec<Brush> {
+        CurveClassic {
+            n: 8,
+            ri0: 32.0,
+            ro0: 64.0,
+            ri1: 32.0,
+            ro1: 64.0,
+            theta0: 0.0,
+            theta1: 90.0,
+            t: 8.0,
+        }
+        .bake()
+        .unwrap()
+    }
+
+    fn shared_vertices(a: &Brush, b: &Brush) -> usize {
+        a.vertices
+            .iter()
+            .filter(|vertex| b.vertices.contains(vertex))
+            .count()
+    }
+
+    #[test]
+    fn snap_curve() {
+        let brushes = curve();
+        for grid in [1.0, 0.125] {
+            let snapped = snap_to_grid(&brushes, grid).unwrap();
+            assert_eq!(snapped.len(), brushes.len());
+            for brush in &snapped {
+                for vertex in &brush.vertices {
+                    assert_eq!(*vertex, (*vertex / grid).round() * grid);
+                }
+                assert_eq!(brush.to_sides_unique().len(), 6);
+            }
+            for (a, b) in snapped.iter().zip(&snapped[1..]) {
+                assert_eq!(shared_vertices(a, b), 4);
+            }
+        }
+    }
+
+    #[test]
+    fn snap_errors() {
+        assert_eq!(
+            snap_to_grid(&curve(), 0.0).unwrap_err(),
+            SnapError::InvalidGrid(0.0)
+        );
+
+        // A sliver thinner than the grid collapses.
+        let vertices = [
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(16.0, 0.0, 0.0),
+            DVec3::new(0.0, 16.0, 0.0),
+            DVec3::new(0.0, 0.0, 0.25),
+            DVec3::new(16.0, 0.0, 0.25),
+            DVec3::new(0.0, 16.0, 0.25),
+        ];
+        let brush = Brush::try_from_vertices(&vertices, None).unwrap();
+        assert!(snap_to_grid(std::slice::from_ref(&brush), 0.125).is_ok());
+        assert!(matches!(
+            snap_to_grid(&[brush], 1.0).unwrap_err(),
+            SnapError::Inverted { brush: 0, .. }
+        ));
+
+        // A sloped top whose corners round to different heights bends.
+        let top = |x: f64, y: f64| DVec3::new(x, y, 10.0 + 0.06 * x + 0.09 * y);
+        let vertices = [
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(10.0, 0.0, 0.0),
+            DVec3::new(10.0, 7.0, 0.0),
+            DVec3::new(0.0, 7.0, 0.0),
+            top(0.0, 0.0),
+            top(10.0, 0.0),
+            top(10.0, 7.0),
+            top(0.0, 7.0),
+        ];
+        let brush = Brush::try_from_vertices(&vertices, None).unwrap();
+        assert!(matches!(
+            snap_to_grid(&[brush], 1.0).unwrap_err(),
+            SnapError::NonPlanar { brush: 0, .. }
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Moving, rotating, scaling, and mirroring brushes.
+
+use crate::map::geometry::{Brush, Plane};
+use glam::{DAffine3, DMat3, DQuat, DVec3, EulerRot};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TransformError {
+    #[error("The transform is not invertible, so it would flatten every brush.")]
+    Singular,
+    #[error("Brush {brush} collapses to nothing after the transform.")]
+    Degenerate { brush: usize },
+}
+
+/// Affine transforms of brushes, implemented for a single [Brush] and for a [Vec] of them, such as
+/// the output of a curve.
+///
+/// Transforms that turn brushes inside out, such as mirroring or scaling by a negative factor,
+/// reverse the winding of every side so that sides keep facing outwards. Materials are kept as
+/// they are; since texture alignment is relative to the world, textures may shift on moved sides.
+///
+/// Every transform fails if it is not invertible, or if rounding collapses a brush, which can
+/// happen when brushes are moved very far from the origin.
+pub trait Transform: Sized {
+    fn transform(&self, transform: DAffine3) -> Result<Self, TransformError>;
+
+    fn translate(&self, offset: DVec3) -> Result<Self, TransformError> {
+        self.transform(DAffine3::from_translation(offset))
+    }
+
+    /// Rotate about the origin.
+    fn rotate(&self, rotation: DQuat) -> Result<Self, TransformError> {
+        self.transform(DAffine3::from_quat(rotation))
+    }
+
+    /// Rotate about the origin by Euler angles in degrees, applied in the given order.
+    fn rotate_euler(
+        &self,
+        order: EulerRot,
+        a: f64,
+        b: f64,
+        c: f64,
+    ) -> Result<Self, TransformError> {
+        self.rotate(DQuat::from_euler(
+            order,
+            a.to_radians(),
+            b.to_radians(),
+            c.to_radians(),
+        ))
+    }
+
+    /// Scale about the origin by the same factor along every axis.
+    fn scale(&self, factor: f64) -> Result<Self, TransformError> {
+        self.scale_xyz(DVec3::splat(factor))
+    }
+
+    /// Scale about the origin by a different factor along each axis.
+    fn scale_xyz(&self, factors: DVec3) -> Result<Self, TransformError> {
+        self.transform(DAffine3::from_scale(factors))
+    }
+
+    /// Reflect across a plane.
+    fn mirror(&self, plane: Plane) -> Result<Self, TransformError> {
+        let Plane { normal, dist } = plane;
+        let outer = DMat3::from_cols(normal * normal.x, normal * normal.y, normal * normal.z);
+        let reflection = DMat3::IDENTITY - 2.0 * outer;
+        self.transform(DAffine3::from_mat3_translation(
+            reflection,
+            2.0 * dist * normal,
+        ))
+    }
+}
+
+impl Transform for Brush {
+    fn transform(&self, transform: DAffine3) -> Result<Self, TransformError> {
+        transform_brush(self, transform, 0)
+    }
+}
+
+impl Transform for Vec<Brush> {
+    fn transform(&self, transform: DAffine3) -> Result<Self, TransformError> {
+        self.iter()
+            .enumerate()
+            .map(|(brush, b)| transform_brush(b, transform, brush))
+            .collect()
+    }
+}
+
+fn transform_brush(
+    brush: &Brush,
+    transform: DAffine3,
+    index: usize,
+) -> Result<Brush, TransformError> {
+    let determinant = transform.matrix3.determinant();
+    if !transform.is_finite() || determinant == 0.0 {
+        return Err(TransformError::Singular);
+    }
+
+    let mut result = Brush {
+        vertices: brush
+            .vertices
+            .iter()
+            .map(|vertex| transform.transform_point3(*vertex) + DVec3::ZERO)
+            .collect(),
+        sides: brush.sides.clone(),
+    };
+    if determinant < 0.0 {
+        for ([_, i1, i2], _) in result.sides.iter_mut() {
+            std::mem::swap(i1, i2);
+        }
+    }
+
+    if result.triangles().any(|side| side.geom.normal().is_none()) || result.volume() <= 0.0 {
+        return Err(TransformError::Degenerate { brush: index });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Curve;
+    use crate::curve::curve_classic::CurveClassic;
+
+    fn curve() -> Vec<Brush> {
+        CurveClassic {
+            n: 4,
+            ri0: 32.0,
+            ro0: 64.0,
+            ri1: 32.0,
+            ro1: 64.0,
+            theta0: 0.0,
+            theta1: 90.0,
+            t: 8.0,
+        }
+        .bake()
+        .unwrap()
+    }
+
+    fn outward(brush: &Brush) -> bool {
+        let centroid = brush.vertices().iter().sum::<DVec3>() / brush.vertices().len() as f64;
+        brush.triangles().all(|side| {
+            let plane = side.geom.plane().unwrap();
+            plane.distance_to(centroid) < 0.0
+        })
+    }
+
+    #[test]
+    fn rigid_transforms() {
+        let brushes = curve();
+        let volume = |brushes: &[Brush]| brushes.iter().map(Brush::volume).sum::<f64>();
+
+        let moved = brushes.translate(DVec3::new(512.0, -256.0, 64.0)).unwrap();
+        assert_eq!(
+            moved[0].vertices()[0],
+            brushes[0].vertices()[0] + DVec3::new(512.0, -256.0, 64.0)
+        );
+
+        let rotated = brushes.rotate_euler(EulerRot::ZYX, 90.0, 0.0, 0.0).unwrap();
+        let expected =
+            DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2) * brushes[1].vertices()[2];
+        assert!(rotated[1].vertices()[2].distance(expected) < 1e-9);
+
+        let mirrored = brushes.mirror(Plane::new(DVec3::X, 16.0).unwrap()).unwrap();
+        assert!((mirrored[0].vertices()[0].x - (32.0 - brushes[0].vertices()[0].x)).abs() < 1e-9);
+
+        for result in [moved, rotated, mirrored] {
+            assert!((volume(&result) - volume(&brushes)).abs() < 1e-6);
+            assert!(result.iter().all(outward));
+        }
+    }
+
+    #[test]
+    fn scale_brushes() {
+        let brushes = curve();
+        let before = brushes[0].volume();
+
+        let doubled = brushes.scale(2.0).unwrap();
+        assert!((doubled[0].volume() - 8.0 * before).abs() < 1e-6);
+
+        // A negative factor mirrors, so the winding has to be reversed.
+        let flipped = brushes[0].scale_xyz(DVec3::new(1.0, -0.5, 1.0)).unwrap();
+        assert!((flipped.volume() - 0.5 * before).abs() < 1e-6);
+        assert!(outward(&flipped));
+
+        assert_eq!(
+            brushes.scale_xyz(DVec3::new(1.0, 0.0, 1.0)).unwrap_err(),
+            TransformError::Singular
+        );
+        assert_eq!(
+            brushes.translate(DVec3::splat(1e20)).unwrap_err(),
+            TransformError::Degenerate { brush: 0 }
+        );
+    }
+}
@@ -0,0 +1,387 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Options controlling how a [QMap] is written out as text.
+
+use crate::map::geometry::Brush;
+use crate::map::qmap::{QEntity, QMap};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+
+/// Options for writing a [QMap] as text.
+///
+/// The default options match `QMap`'s implementation of `Display`. For output that stays the same
+/// between runs, e.g. to keep generated maps in version control, set `sort_keys`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapWriter {
+    /// Number of digits after the decimal point in the points of each side.
+    pub precision: usize,
+    /// Round the points of each side to whole numbers. This moves sides slightly, so it suits
+    /// large brushes better than fine detail. A side whose points would collapse onto a line when
+    /// rounded keeps `precision` digits instead.
+    pub integer_coordinates: bool,
+    /// Write `// entity i` and `// brush i` comments. Metadata is always written, since editors
+    /// like TrenchBroom rely on it.
+    pub comments: bool,
+    pub line_ending: LineEnding,
+    pub format: MapFormat,
+    /// Write each entity's keys in order, with `classname` first. Otherwise, the order is
+    /// unspecified and may change between runs.
+    pub sort_keys: bool,
+}
+
+impl Default for MapWriter {
+    fn default() -> Self {
+        Self {
+            precision: 6,
+            integer_coordinates: false,
+            comments: true,
+            line_ending: LineEnding::default(),
+            format: MapFormat::default(),
+            sort_keys: false,
+        }
+    }
+}
+
+impl MapWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write `map` as text according to these options.
+    pub fn bake(self, map: &QMap) -> impl Display + use<'_> {
+        map.bake_with(self)
+    }
+
+    /// Write `map` as text to `out` according to these options. See [QMap::write_to].
+    pub fn write_to(self, map: &QMap, mut out: impl Write) -> io::Result<()> {
+        write!(out, "{}", self.bake(map))?;
+        out.flush()
+    }
+
+    // Everything written before the first entity. A TrenchBroom "Format" line is changed to name
+    // the format actually being written.
+    pub(crate) fn header(self, metadata: &[String]) -> impl Display + use<'_> {
+        struct HeaderDisp<'a>(&'a [String], MapWriter);
+        impl Display for HeaderDisp<'_> {
+            fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+                let (metadata, writer) = (self.0, self.1);
+                let eol = writer.line_ending;
+                for metadata_line in metadata.iter().flat_map(|meta| meta.lines()) {
+                    match (
+                        metadata_line.starts_with("Format:"),
+                        writer.format.tb_name(),
+                    ) {
+                        (true, Some(tb_name)) => write!(f, "// Format: {tb_name}{eol}")?,
+                        _ => write!(f, "// {metadata_line}{eol}")?,
+                    }
+                }
+                if writer.format == MapFormat::BrushDef3 {
+                    write!(f, "Version 2{eol}")?;
+                }
+                Ok(())
+            }
+        }
+        HeaderDisp(metadata, self)
+    }
+
+    // The parameters of an entity in the order they should be written.
+    pub(crate) fn ordered(self, parameters: &HashMap<String, String>) -> Vec<(&str, &str)> {
+        let mut parameters: Vec<_> = parameters
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        // Valve 220 maps mark themselves as such in the worldspawn.
+        if self.format == MapFormat::Valve220
+            && parameters.contains(&("classname", "worldspawn"))
+            && !parameters.iter().any(|(key, _)| *key == "mapversion")
+        {
+            parameters.push(("mapversion", "220"));
+        }
+        if self.sort_keys {
+            parameters.sort_by_key(|(key, _)| (*key != "classname", *key));
+        }
+        parameters
+    }
+}
+
+/// Writes a map a piece at a time, so the whole map never needs to be held in memory.
+///
+/// Start each entity with [begin_entity](Self::begin_entity) and add its brushes one by one with
+/// [write_brush](Self::write_brush). Call [finish](Self::finish) when done, or the last entity is
+/// left open.
+#[derive(Debug)]
+pub struct MapStream<W: Write> {
+    out: W,
+    writer: MapWriter,
+    entities: usize,
+    // The number of brushes written to the open entity, if there is one.
+    brushes: Option<usize>,
+}
+
+impl<W: Write> MapStream<W> {
+    /// Start a map by writing its metadata.
+    pub fn new(mut out: W, writer: MapWriter, metadata: &[String]) -> io::Result<Self> {
+        write!(out, "{}", writer.header(metadata))?;
+        Ok(Self {
+            out,
+            writer,
+            entities: 0,
+            brushes: None,
+        })
+    }
+
+    /// Close the open entity, if any, and start a new one with the given parameters.
+    pub fn begin_entity(&mut self, parameters: &HashMap<String, String>) -> io::Result<()> {
+        self.end_entity()?;
+        let eol = self.writer.line_ending;
+        if self.writer.comments {
+            write!(self.out, "// entity {}{eol}", self.entities)?;
+        }
+        write!(self.out, "{{{eol}")?;
+        for (key, value) in self.writer.ordered(parameters) {
+            write!(self.out, "\"{key}\" \"{value}\"{eol}")?;
+        }
+        self.entities += 1;
+        self.brushes = Some(0);
+        Ok(())
+    }
+
+    /// Add a brush to the open entity.
+    pub fn write_brush(&mut self, brush: &Brush) -> io::Result<()> {
+        let Some(brushes) = self.brushes.as_mut() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a brush must belong to an entity",
+            ));
+        };
+        if self.writer.comments {
+            write!(self.out, "// brush {brushes}{}", self.writer.line_ending)?;
+        }
+        *brushes += 1;
+        write!(self.out, "{}", brush.bake(self.writer))
+    }
+
+    /// Write a whole entity and its brushes.
+    pub fn write_entity(&mut self, entity: &QEntity) -> io::Result<()> {
+        self.begin_entity(&entity.parameters)?;
+        for brush in &entity.brushes {
+            self.write_brush(brush)?;
+        }
+        Ok(())
+    }
+
+    /// Close the open entity and flush the output, returning it.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.end_entity()?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn end_entity(&mut self) -> io::Result<()> {
+        if self.brushes.take().is_some() {
+            write!(self.out, "}}{}", self.writer.line_ending)?;
+        }
+        Ok(())
+    }
+}
+
+/// The syntax used to write each brush.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MapFormat {
+    /// Quake3's standard format, giving each side three points and a texture offset, rotation,
+    /// and scale.
+    #[default]
+    Standard,
+    /// Valve 220, which also gives each side its texture axes. The axes lie in each face's plane,
+    /// so textures are not stretched on faces at an angle; on axial faces they look the same as in
+    /// the standard format.
+    Valve220,
+    /// Doom3's `brushDef3`, giving each side a plane equation and a texture matrix built from the
+    /// same axes as Valve 220. Doom3 measures textures relative to their size, so the matrix
+    /// assumes a 128 by 128 texture.
+    BrushDef3,
+}
+
+impl MapFormat {
+    // The name TrenchBroom gives the format in a map's metadata, if it differs from the default.
+    fn tb_name(self) -> Option<&'static str> {
+        match self {
+            Self::Standard => None,
+            Self::Valve220 => Some("Quake3 (Valve)"),
+            Self::BrushDef3 => Some("Doom3"),
+        }
+    }
+}
+
+impl Display for MapFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standard => write!(f, "Standard"),
+            Self::Valve220 => write!(f, "Valve 220"),
+            Self::BrushDef3 => write!(f, "brushDef3"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lf => f.write_str("\n"),
+            Self::CrLf => f.write_str("\r\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::entity::SimpleWorldspawn;
+    use crate::map::geometry::Brush;
+    use crate::map::qmap::QEntity;
+    use glam::DVec3;
+
+    fn map() -> QMap {
+        let vertices = [
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(64.0, 0.0, 0.0),
+            DVec3::new(0.0, 64.0, 0.0),
+            DVec3::new(0.0, 0.0, 64.25),
+        ];
+        let brush = Brush::try_from_vertices(&vertices, None).unwrap();
+        let mut entity = QEntity::from(SimpleWorldspawn::new(vec![brush]));
+        entity
+            .parameters
+            .insert(String::from("message"), String::from("hi"));
+        entity
+            .parameters
+            .insert(String::from("author"), String::from("me"));
+        QMap::new(vec![entity]).with_tb_neverball_metadata()
+    }
+
+    #[test]
+    fn default_matches_display() {
+        let map = map();
+        assert_eq!(MapWriter::default().bake(&map).to_string(), map.to_string());
+    }
+
+    #[test]
+    fn stream_matches_display() {
+        let map = map();
+        let mut out = Vec::new();
+        map.write_to(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), map.to_string());
+
+        let writer = MapWriter {
+            sort_keys: true,
+            ..Default::default()
+        };
+        let mut stream = MapStream::new(Vec::new(), writer, &map.metadata).unwrap();
+        assert_eq!(
+            stream
+                .write_brush(&map.entities[0].brushes[0])
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+        stream.begin_entity(&map.entities[0].parameters).unwrap();
+        for brush in &map.entities[0].brushes {
+            stream.write_brush(brush).unwrap();
+        }
+        stream.write_entity(&map.entities[0]).unwrap();
+        let text = String::from_utf8(stream.finish().unwrap()).unwrap();
+
+        let twice = QMap {
+            entities: vec![map.entities[0].clone(), map.entities[0].clone()],
+            ..map
+        };
+        assert_eq!(text, writer.bake(&twice).to_string());
+    }
+
+    #[test]
+    fn io_errors() {
+        struct Full;
+        impl Write for Full {
+            fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let error = map().write_to(Full).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::StorageFull);
+    }
+
+    #[test]
+    fn valve_220() {
+        let writer = MapWriter {
+            format: MapFormat::Valve220,
+            sort_keys: true,
+            ..Default::default()
+        };
+        let text = writer.bake(&map()).to_string();
+        assert!(text.starts_with("// Game: Neverball\n// Format: Quake3 (Valve)\n"));
+        assert!(text.contains("\"mapversion\" \"220\"\n"));
+        // The bottom face is projected from above, along +x and -y.
+        assert!(text.contains("mtrl/invisible [ 1 0 0 0 ] [ 0 -1 0 0 ] 0 0.5 0.5 0 0 0"));
+    }
+
+    #[test]
+    fn brush_def_3() {
+        let writer = MapWriter {
+            format: MapFormat::BrushDef3,
+            precision: 1,
+            ..Default::default()
+        };
+        let text = writer.bake(&map()).to_string();
+        assert!(text.starts_with("// Game: Neverball\n// Format: Doom3\nVersion 2\n"));
+        assert!(text.contains("{\nbrushDef3\n{\n"));
+        assert!(text.contains(
+            "( 0.0 0.0 -1.0 0.0 ) ( ( 0 -0.015625 0 ) ( -0.015625 0 0 ) ) \"mtrl/invisible\" 0 0 0"
+        ));
+        assert_eq!(text.matches("brushDef3").count(), 1);
+    }
+
+    #[test]
+    fn compact_sorted_output() {
+        let writer = MapWriter {
+            integer_coordinates: true,
+            comments: false,
+            line_ending: LineEnding::CrLf,
+            sort_keys: true,
+            ..Default::default()
+        };
+        let text = writer.bake(&map()).to_string();
+        assert!(text.starts_with(
+            "// Game: Neverball\r\n// Format: Quake3\r\n{\r\n\"classname\" \"worldspawn\"\r\n\"author\" \"me\"\r\n\"message\" \"hi\"\r\n{\r\n"
+        ));
+        assert!(!text.contains("// entity"));
+        assert!(!text.contains("// brush"));
+        for side in text.lines().filter(|line| line.starts_with('(')) {
+            let (points, _) = side.split_once("mtrl").unwrap();
+            assert!(!points.contains('.'));
+        }
+        assert!(text.contains("( 0 0 64 )"));
+        assert_eq!(text.matches('\n').count(), text.matches("\r\n").count());
+
+        let writer = MapWriter {
+            precision: 2,
+            ..Default::default()
+        };
+        assert!(
+            writer
+                .bake(&map())
+                .to_string()
+                .contains("( 0.00 0.00 64.25 )")
+        );
+    }
+}
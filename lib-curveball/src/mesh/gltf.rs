@@ -0,0 +1,144 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Export to [glTF](https://www.khronos.org/gltf/), as a single binary `.glb` file.
+
+use super::{triangles, y_up};
+use crate::map::geometry::Brush;
+use std::io::{self, Write};
+
+/// Write brushes as a binary glTF file, with one mesh and node per brush.
+///
+/// Points are converted to put Y up and are otherwise left in map units, so the scene may need
+/// scaling down once imported.
+pub fn write_glb<'a, W>(
+    brushes: impl IntoIterator<Item = &'a Brush>,
+    mut writer: W,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let mut bin: Vec<u8> = Vec::new();
+    let mut buffer_views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut meshes = Vec::new();
+    let mut nodes = Vec::new();
+
+    for (i, brush) in brushes.into_iter().enumerate() {
+        let (positions, normals): (Vec<_>, Vec<_>) = triangles(brush)
+            .flat_map(|(points, normal)| {
+                points.map(|point| (y_up(point).as_vec3(), y_up(normal).as_vec3()))
+            })
+            .unzip();
+        // glTF does not allow empty accessors.
+        if positions.is_empty() {
+            continue;
+        }
+
+        let min = positions.iter().copied().reduce(|a, b| a.min(b)).unwrap();
+        let max = positions.iter().copied().reduce(|a, b| a.max(b)).unwrap();
+        for (attribute, data) in [("POSITION", &positions), ("NORMAL", &normals)] {
+            let offset = bin.len();
+            for value in data.iter().flat_map(|v| v.to_array()) {
+                bin.extend_from_slice(&value.to_le_bytes());
+            }
+            buffer_views.push(format!(
+                r#"{{"buffer":0,"byteOffset":{offset},"byteLength":{},"target":34962}}"#,
+                bin.len() - offset
+            ));
+            let bounds = match attribute {
+                "POSITION" => format!(
+                    r#","min":[{},{},{}],"max":[{},{},{}]"#,
+                    min.x, min.y, min.z, max.x, max.y, max.z
+                ),
+                _ => String::new(),
+            };
+            accessors.push(format!(
+                r#"{{"bufferView":{},"componentType":5126,"count":{},"type":"VEC3"{bounds}}}"#,
+                buffer_views.len() - 1,
+                data.len()
+            ));
+        }
+
+        let position = accessors.len() - 2;
+        let normal = accessors.len() - 1;
+        meshes.push(format!(
+            r#"{{"name":"brush_{i}","primitives":[{{"attributes":{{"POSITION":{position},"NORMAL":{normal}}}}}]}}"#
+        ));
+        nodes.push(format!(
+            r#"{{"name":"brush_{i}","mesh":{}}}"#,
+            meshes.len() - 1
+        ));
+    }
+
+    let mut json = String::from(r#"{"asset":{"version":"2.0","generator":"lib-curveball"}"#);
+    if nodes.is_empty() {
+        json.push_str(r#","scene":0,"scenes":[{}]"#);
+    } else {
+        let node_indices = (0..nodes.len()).map(|i| i.to_string()).collect::<Vec<_>>();
+        json.push_str(&format!(
+            r#","scene":0,"scenes":[{{"nodes":[{}]}}],"nodes":[{}],"meshes":[{}],"accessors":[{}],"bufferViews":[{}],"buffers":[{{"byteLength":{}}}]"#,
+            node_indices.join(","),
+            nodes.join(","),
+            meshes.join(","),
+            accessors.join(","),
+            buffer_views.join(","),
+            bin.len()
+        ));
+    }
+    json.push('}');
+
+    // Chunks must be aligned to four bytes: JSON is padded with spaces, binary data with zeros.
+    let mut json = json.into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let mut length = 12 + 8 + json.len();
+    if !bin.is_empty() {
+        length += 8 + bin.len();
+    }
+    let length = u32::try_from(length)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too much geometry for glTF"))?;
+
+    writer.write_all(b"glTF")?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&length.to_le_bytes())?;
+    writer.write_all(&(json.len() as u32).to_le_bytes())?;
+    writer.write_all(b"JSON")?;
+    writer.write_all(&json)?;
+    if !bin.is_empty() {
+        writer.write_all(&(bin.len() as u32).to_le_bytes())?;
+        writer.write_all(b"BIN\0")?;
+        writer.write_all(&bin)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::DVec3;
+
+    #[test]
+    fn glb_layout() {
+        let vertices = [DVec3::ZERO, DVec3::X, DVec3::Y, DVec3::Z];
+        let tetrahedron = Brush::try_from_vertices(&vertices, None).unwrap();
+        let mut out = Vec::new();
+        write_glb([&tetrahedron, &tetrahedron], &mut out).unwrap();
+
+        let word = |offset: usize| u32::from_le_bytes(out[offset..offset + 4].try_into().unwrap());
+        assert_eq!(&out[0..4], b"glTF");
+        assert_eq!(word(8) as usize, out.len());
+
+        let json_length = word(12) as usize;
+        assert_eq!(&out[16..20], b"JSON");
+        let json = std::str::from_utf8(&out[20..20 + json_length]).unwrap();
+        assert!(json.contains(r#""name":"brush_1""#));
+        assert!(json.contains(r#""min":[0,0,-1],"max":[1,1,0]"#));
+
+        // Two brushes of four triangles, each vertex having a position and a normal.
+        let bin_start = 20 + json_length;
+        assert_eq!(word(bin_start) as usize, 2 * 4 * 3 * 2 * 12);
+        assert_eq!(&out[bin_start + 4..bin_start + 8], b"BIN\0");
+    }
+}
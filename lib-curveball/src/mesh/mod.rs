@@ -0,0 +1,31 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Exporting brushes as triangle meshes, for viewing or printing them outside of a level editor.
+
+use crate::map::geometry::{Brush, Side, SideGeom};
+use glam::DVec3;
+
+pub mod gltf;
+pub mod obj;
+pub mod stl;
+
+// The triangles of a brush with their outward normals, wound counter-clockwise when viewed from
+// outside as most mesh formats expect. Degenerate triangles have no normal and are skipped.
+fn triangles(brush: &Brush) -> impl Iterator<Item = ([DVec3; 3], DVec3)> + use<'_> {
+    brush.triangles().filter_map(
+        |Side {
+             geom: geom @ SideGeom([p0, p1, p2]),
+             mtrl: _,
+         }| Some(([p0, p2, p1], geom.normal()?)),
+    )
+}
+
+// Quake maps put Z up, while OBJ and glTF put Y up. Adding zero turns any negative zeros positive.
+fn y_up(point: DVec3) -> DVec3 {
+    DVec3 {
+        x: point.x,
+        y: point.z,
+        z: -point.y + 0.0,
+    }
+}
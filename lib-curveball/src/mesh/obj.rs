@@ -0,0 +1,150 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Export to [Wavefront OBJ](https://en.wikipedia.org/wiki/Wavefront_.obj_file).
+
+use super::y_up;
+use crate::map::geometry::Brush;
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+/// Write brushes as an OBJ file, with one group per brush.
+///
+/// Each face is given the normal of its plane. Given the name of a material library, such as one
+/// written by [`write_mtl`], the file refers to it and each face uses a material named after its
+/// texture, so faces sharing a texture can be selected together once imported. Without one, no
+/// materials are named. Points are converted to put Y up.
+pub fn write_obj<'a, W>(
+    brushes: impl IntoIterator<Item = &'a Brush>,
+    mtllib: Option<&str>,
+    mut writer: W,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(writer, "# Exported by lib-curveball")?;
+    if let Some(mtllib) = mtllib {
+        writeln!(writer, "mtllib {mtllib}")?;
+    }
+
+    // OBJ indices start at 1 and count up through the whole file.
+    let mut vertex_base = 1;
+    let mut normal_base = 1;
+    for (i, brush) in brushes.into_iter().enumerate() {
+        writeln!(writer, "g brush_{i}")?;
+        for vertex in &brush.vertices {
+            let v = y_up(*vertex);
+            writeln!(writer, "v {} {} {}", v.x, v.y, v.z)?;
+        }
+
+        let faces: Vec<_> = brush
+            .sides
+            .iter()
+            .zip(brush.triangles())
+            .filter_map(|((idx, mtrl), side)| Some((idx, mtrl, side.geom.normal()?)))
+            .collect();
+        for (_, _, normal) in &faces {
+            let n = y_up(*normal);
+            writeln!(writer, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+
+        let mut texture = None;
+        for (j, ([idx0, idx1, idx2], mtrl, _)) in faces.iter().enumerate() {
+            if mtllib.is_some() && texture != Some(&mtrl.texture) {
+                writeln!(writer, "usemtl {}", mtrl.texture)?;
+                texture = Some(&mtrl.texture);
+            }
+            let vn = normal_base + j;
+            // Reverse the winding of the side so it is counter-clockwise from outside.
+            writeln!(
+                writer,
+                "f {}//{vn} {}//{vn} {}//{vn}",
+                vertex_base + idx0,
+                vertex_base + idx2,
+                vertex_base + idx1,
+            )?;
+        }
+
+        vertex_base += brush.vertices.len();
+        normal_base += faces.len();
+    }
+    Ok(())
+}
+
+/// Write a material library with a plain white material for each texture used by the brushes,
+/// to go alongside an OBJ file from [`write_obj`].
+pub fn write_mtl<'a, W>(
+    brushes: impl IntoIterator<Item = &'a Brush>,
+    mut writer: W,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(writer, "# Exported by lib-curveball")?;
+    let textures: BTreeSet<&str> = brushes
+        .into_iter()
+        .flat_map(|brush| &brush.sides)
+        .map(|(_, mtrl)| mtrl.texture.as_str())
+        .collect();
+    for texture in textures {
+        writeln!(writer, "newmtl {texture}")?;
+        writeln!(writer, "Kd 1 1 1")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::geometry::Brush;
+    use glam::DVec3;
+
+    #[test]
+    fn groups_and_indices() {
+        let cube = |offset: DVec3| {
+            let vertices: Vec<_> = (0..8)
+                .map(|i| offset + DVec3::new((i & 1) as f64, (i >> 1 & 1) as f64, (i >> 2) as f64))
+                .collect();
+            Brush::try_from_vertices(&vertices, None).unwrap()
+        };
+        let brushes = [cube(DVec3::ZERO), cube(DVec3::X * 4.0)];
+        let mut out = Vec::new();
+        write_obj(&brushes, Some("brushes.mtl"), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert_eq!(text.lines().filter(|l| l.starts_with("g ")).count(), 2);
+        assert_eq!(text.lines().filter(|l| l.starts_with("v ")).count(), 16);
+        assert_eq!(text.lines().filter(|l| l.starts_with("f ")).count(), 24);
+        assert!(text.contains("\nmtllib brushes.mtl\n"));
+        assert!(text.contains("usemtl mtrl/invisible"));
+        // The faces of the second brush refer to its own vertices.
+        let last_face = text.lines().rfind(|l| l.starts_with("f ")).unwrap();
+        for index in last_face.split_whitespace().skip(1) {
+            let v: usize = index.split("//").next().unwrap().parse().unwrap();
+            assert!((9..=16).contains(&v));
+        }
+    }
+
+    #[test]
+    fn optional_materials() {
+        let vertices = [DVec3::ZERO, DVec3::X, DVec3::Y, DVec3::Z];
+        let mut brush = Brush::try_from_vertices(&vertices, None).unwrap();
+        brush.sides[0].1.texture = "mtrl/turf-green".to_string();
+
+        let mut out = Vec::new();
+        write_obj([&brush], None, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(!text.contains("mtllib") && !text.contains("usemtl"));
+        assert_eq!(text.lines().filter(|l| l.starts_with("f ")).count(), 4);
+
+        let mut out = Vec::new();
+        write_mtl([&brush], &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(
+            text.lines()
+                .filter(|l| l.starts_with("newmtl "))
+                .collect::<Vec<_>>(),
+            ["newmtl mtrl/invisible", "newmtl mtrl/turf-green"]
+        );
+    }
+}
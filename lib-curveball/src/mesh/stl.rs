@@ -0,0 +1,115 @@
+// Copyright 2025 Jordan Johnson
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Export to [STL](https://en.wikipedia.org/wiki/STL_(file_format)), in either its binary or ASCII
+//! form.
+//!
+//! STL has no notion of separate objects, so all brushes end up in one solid. Points keep Quake's
+//! coordinates, with Z up, as slicers for 3D printing expect.
+
+use super::triangles;
+use crate::map::geometry::Brush;
+use glam::DVec3;
+use std::io::{self, Write};
+
+/// Write brushes as a binary STL file.
+pub fn write_stl_binary<'a, W>(
+    brushes: impl IntoIterator<Item = &'a Brush>,
+    mut writer: W,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let facets: Vec<_> = brushes.into_iter().flat_map(triangles).collect();
+    let count = u32::try_from(facets.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many triangles for STL"))?;
+
+    let mut header = [0u8; 80];
+    let name = b"Exported by lib-curveball";
+    header[..name.len()].copy_from_slice(name);
+    writer.write_all(&header)?;
+    writer.write_all(&count.to_le_bytes())?;
+
+    let write_point = |writer: &mut W, point: DVec3| -> io::Result<()> {
+        for component in point.as_vec3().to_array() {
+            writer.write_all(&component.to_le_bytes())?;
+        }
+        Ok(())
+    };
+    for (points, normal) in facets {
+        write_point(&mut writer, normal)?;
+        for point in points {
+            write_point(&mut writer, point)?;
+        }
+        // Attribute byte count, unused.
+        writer.write_all(&0u16.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Write brushes as an ASCII STL file, naming the solid `name`.
+pub fn write_stl_ascii<'a, W>(
+    brushes: impl IntoIterator<Item = &'a Brush>,
+    name: &str,
+    mut writer: W,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(writer, "solid {name}")?;
+    for (points, normal) in brushes.into_iter().flat_map(triangles) {
+        writeln!(
+            writer,
+            "facet normal {:e} {:e} {:e}",
+            normal.x, normal.y, normal.z
+        )?;
+        writeln!(writer, "  outer loop")?;
+        for point in points {
+            writeln!(
+                writer,
+                "    vertex {:e} {:e} {:e}",
+                point.x, point.y, point.z
+            )?;
+        }
+        writeln!(writer, "  endloop")?;
+        writeln!(writer, "endfacet")?;
+    }
+    writeln!(writer, "endsolid {name}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tetrahedron() -> Brush {
+        let vertices = [DVec3::ZERO, DVec3::X, DVec3::Y, DVec3::Z];
+        Brush::try_from_vertices(&vertices, None).unwrap()
+    }
+
+    #[test]
+    fn binary_and_ascii() {
+        let brushes = [tetrahedron(), tetrahedron()];
+
+        let mut binary = Vec::new();
+        write_stl_binary(&brushes, &mut binary).unwrap();
+        assert_eq!(binary.len(), 84 + 8 * 50);
+        assert_eq!(binary[80..84], 8u32.to_le_bytes());
+
+        // The first facet's vertices wind counter-clockwise around its normal.
+        let float = |offset: usize| {
+            f32::from_le_bytes(binary[offset..offset + 4].try_into().unwrap()) as f64
+        };
+        let vec = |offset: usize| DVec3::new(float(offset), float(offset + 4), float(offset + 8));
+        let (normal, p0, p1, p2) = (vec(84), vec(96), vec(108), vec(120));
+        assert!((p1 - p0).cross(p2 - p0).normalize().dot(normal) > 0.99);
+
+        let mut ascii = Vec::new();
+        write_stl_ascii(&brushes, "track", &mut ascii).unwrap();
+        let text = String::from_utf8(ascii).unwrap();
+        assert!(text.starts_with("solid track\n"));
+        assert!(text.ends_with("endsolid track\n"));
+        assert_eq!(text.matches("facet normal").count(), 8);
+        assert_eq!(text.matches("vertex").count(), 24);
+    }
+}